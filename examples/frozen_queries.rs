@@ -0,0 +1,83 @@
+//! Compares the time of the same queries on a [Searcher] and on the
+//! [FrozenSearcher] saved from it, the frozen one reading the bitmaps of the
+//! matched words in place on each query.
+//!
+//! Run with `cargo run --release --example frozen_queries`.
+
+use searcher::{AttrProps, DocId, FrozenSearcher, SearchQuery, Searcher};
+use std::time::{Duration, Instant};
+
+const DOCS: u32 = 100_000;
+const RUNS: u32 = 20;
+
+const WORDS: &[&str] = &[
+    "account",
+    "balance",
+    "contract",
+    "customer",
+    "delivery",
+    "invoice",
+    "order",
+    "payment",
+    "product",
+    "quantity",
+    "receipt",
+    "shipment",
+    "supplier",
+    "transfer",
+    "warehouse",
+];
+
+fn main() {
+    let mut searcher = Searcher::new();
+    searcher.set_attribute("text".into(), AttrProps::default().culture(0));
+
+    for id in 0..DOCS {
+        let words = (0..4)
+            .map(|i| WORDS[(id as usize * 7 + i * 3) % WORDS.len()])
+            .collect::<Vec<_>>();
+
+        let value = format!(
+            "{} {}{}",
+            words.join(" "),
+            WORDS[id as usize % WORDS.len()],
+            id
+        );
+        searcher.insert_doc_attribute(DocId::from(id), "text", &value);
+    }
+
+    let mut bytes = Vec::new();
+    searcher.save_frozen_to(&mut bytes).unwrap();
+
+    let frozen = FrozenSearcher::open(bytes).unwrap();
+
+    for text in [
+        "invoice",
+        "invoce",
+        "pay*",
+        "*ship*",
+        "order payment",
+        "'receipt'",
+    ] {
+        let query = SearchQuery::new(0, text);
+        let live = time(|| searcher.query(&query).len());
+        let frozen = time(|| frozen.query(&query).len());
+
+        println!(
+            "{text:>16}: {} docs, live {:>10.3?}, frozen {:>10.3?}",
+            searcher.query(&query).len(),
+            live / RUNS,
+            frozen / RUNS
+        );
+    }
+}
+
+fn time<F: FnMut() -> usize>(mut f: F) -> Duration {
+    let start = Instant::now();
+
+    for _ in 0..RUNS {
+        std::hint::black_box(f());
+    }
+
+    start.elapsed()
+}
@@ -0,0 +1,183 @@
+use crate::{
+    normalizer::DEFAULT_NORMALIZER, searcher::Attr, tokenizer::DEFAULT_TOKENIZER, Normalizer,
+    Tokenizer,
+};
+use fxhash::FxHashMap;
+use std::{ops::Range, ptr, sync::Arc};
+
+/// The tokenizer and the normalizer producing the words of a value or a query.
+#[derive(Clone, Copy)]
+pub(crate) struct Analyzer<'a> {
+    pub(crate) normalizer: &'a dyn Normalizer,
+    pub(crate) tokenizer: &'a dyn Tokenizer,
+}
+
+impl Analyzer<'_> {
+    /// Checks if two analyzers have the same tokenizer and normalizer.
+    pub(crate) fn is_same(&self, other: &Analyzer) -> bool {
+        ptr::addr_eq(self.normalizer, other.normalizer)
+            && ptr::addr_eq(self.tokenizer, other.tokenizer)
+    }
+
+    /// Calls `sink` with each normalized word of `text` and its byte range in
+    /// `text`, in order.
+    pub(crate) fn words(&self, text: &str, sink: &mut dyn FnMut(&str, Range<usize>)) {
+        let mut word = String::new();
+
+        self.tokenizer.tokenize(text, &mut |token, range| {
+            word.clear();
+            self.normalizer.normalize(token, &mut word);
+
+            if !word.is_empty() {
+                sink(&word, range);
+            }
+        });
+    }
+}
+
+impl Default for Analyzer<'_> {
+    fn default() -> Self {
+        Self {
+            normalizer: &DEFAULT_NORMALIZER,
+            tokenizer: &DEFAULT_TOKENIZER,
+        }
+    }
+}
+
+/// The tokenizers and normalizers of a searcher, by name for the attributes
+/// referring to one and by culture for the other attributes and the queries.
+///
+/// They are code, they are not saved with the searcher and must be registered
+/// again after it is loaded.
+#[derive(Clone, Default)]
+pub(crate) struct Analyzers {
+    normalizers: Registry<dyn Normalizer>,
+    tokenizers: Registry<dyn Tokenizer>,
+}
+
+impl Analyzers {
+    /// Gets the analyzer of an attribute, the tokenizer and normalizer of its
+    /// culture being used when it does not name ones, `None` when it names
+    /// ones not registered.
+    pub(crate) fn attr(&self, a: &Attr) -> Option<Analyzer<'_>> {
+        self.attr_in(a, a.culture)
+    }
+
+    /// Gets the analyzer of an attribute as if it was of a culture.
+    pub(crate) fn attr_in(&self, a: &Attr, culture: Option<u8>) -> Option<Analyzer<'_>> {
+        let default = Analyzer::default();
+
+        Some(Analyzer {
+            normalizer: self
+                .normalizers
+                .attr(a.normalizer.as_deref(), culture)?
+                .unwrap_or(default.normalizer),
+            tokenizer: self
+                .tokenizers
+                .attr(a.tokenizer.as_deref(), culture)?
+                .unwrap_or(default.tokenizer),
+        })
+    }
+
+    pub(crate) fn culture(&self, culture: u8) -> Analyzer<'_> {
+        let default = Analyzer::default();
+
+        Analyzer {
+            normalizer: self
+                .normalizers
+                .attr(None, Some(culture))
+                .flatten()
+                .unwrap_or(default.normalizer),
+            tokenizer: self
+                .tokenizers
+                .attr(None, Some(culture))
+                .flatten()
+                .unwrap_or(default.tokenizer),
+        }
+    }
+
+    /// Gets the first of the names of a normalizer and a tokenizer not
+    /// registered.
+    pub(crate) fn unknown_name<'a>(
+        &self,
+        normalizer: Option<&'a str>,
+        tokenizer: Option<&'a str>,
+    ) -> Option<&'a str> {
+        normalizer
+            .filter(|name| !self.normalizers.by_name.contains_key(*name))
+            .or_else(|| tokenizer.filter(|name| !self.tokenizers.by_name.contains_key(*name)))
+    }
+
+    pub(crate) fn insert_normalizer(&mut self, name: &str, normalizer: Arc<dyn Normalizer>) {
+        self.normalizers.by_name.insert(name.into(), normalizer);
+    }
+
+    pub(crate) fn insert_tokenizer(&mut self, name: &str, tokenizer: Arc<dyn Tokenizer>) {
+        self.tokenizers.by_name.insert(name.into(), tokenizer);
+    }
+
+    pub(crate) fn set_culture_normalizer(&mut self, culture: u8, normalizer: Arc<dyn Normalizer>) {
+        self.normalizers.set_culture(culture, normalizer);
+    }
+
+    pub(crate) fn set_culture_tokenizer(&mut self, culture: u8, tokenizer: Arc<dyn Tokenizer>) {
+        self.tokenizers.set_culture(culture, tokenizer);
+    }
+}
+
+/// The registered implementations of a trait, by culture and by name.
+struct Registry<T: ?Sized> {
+    by_culture: Vec<Option<Arc<T>>>,
+    by_name: FxHashMap<Box<str>, Arc<T>>,
+}
+
+impl<T: ?Sized> Registry<T> {
+    /// Gets the one named, else the one of the culture, `None` when the one
+    /// named is not registered.
+    fn attr(&self, name: Option<&str>, culture: Option<u8>) -> Option<Option<&T>> {
+        match name {
+            Some(name) => self.by_name.get(name).map(|t| Some(&**t)),
+            None => Some(culture.and_then(|c| self.by_culture.get(c as usize)?.as_deref())),
+        }
+    }
+
+    fn set_culture(&mut self, culture: u8, t: Arc<T>) {
+        let index = culture as usize;
+
+        if self.by_culture.len() <= index {
+            self.by_culture.resize(index + 1, None);
+        }
+
+        self.by_culture[index] = Some(t);
+    }
+}
+
+impl<T: ?Sized> Clone for Registry<T> {
+    fn clone(&self) -> Self {
+        Self {
+            by_culture: self.by_culture.clone(),
+            by_name: self.by_name.clone(),
+        }
+    }
+}
+
+impl<T: ?Sized> Default for Registry<T> {
+    fn default() -> Self {
+        Self {
+            by_culture: Vec::new(),
+            by_name: FxHashMap::default(),
+        }
+    }
+}
+
+/// Splits a value into its normalized words with their byte range in the
+/// value, in the order they are indexed.
+pub(crate) fn tokenize(analyzer: Analyzer, value: &str) -> Vec<(String, Range<u32>)> {
+    let mut words = Vec::new();
+
+    analyzer.words(value, &mut |word, range| {
+        words.push((word.to_owned(), range.start as u32..range.end as u32))
+    });
+
+    words
+}
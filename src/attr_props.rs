@@ -1,10 +1,14 @@
-use crate::Direction;
+use crate::{Direction, FuzzyPolicy};
 
-#[derive(Clone, Default)]
+#[derive(Clone, Debug, Default, Eq, PartialEq)]
 pub struct AttrProps {
     pub(crate) culture: Option<u8>,
     pub(crate) direction: Direction,
+    pub(crate) fuzzy_policy: Option<FuzzyPolicy>,
+    pub(crate) normalizer: Option<Box<str>>,
     pub(crate) priority: u8,
+    pub(crate) store: bool,
+    pub(crate) tokenizer: Option<Box<str>>,
 }
 
 impl AttrProps {
@@ -18,8 +22,42 @@ impl AttrProps {
         self
     }
 
+    /// Overrides the fuzzy policy of the queries for the words of this
+    /// attribute.
+    pub fn fuzzy_policy(mut self, fuzzy_policy: FuzzyPolicy) -> Self {
+        self.fuzzy_policy = Some(fuzzy_policy);
+        self
+    }
+
+    /// Normalizes the words of the attribute with the normalizer registered
+    /// under this name with
+    /// [Searcher::register_normalizer](crate::Searcher::register_normalizer),
+    /// instead of the one of its culture. The values are not inserted while
+    /// it is not registered.
+    pub fn normalizer(mut self, name: &str) -> Self {
+        self.normalizer = Some(name.into());
+        self
+    }
+
     pub fn priority(mut self, priority: u8) -> Self {
         self.priority = priority;
         self
     }
+
+    /// Keeps the raw values of the attribute, to be retrieved with
+    /// [Searcher::get_doc_attr_value](crate::Searcher::get_doc_attr_value).
+    pub fn store(mut self, store: bool) -> Self {
+        self.store = store;
+        self
+    }
+
+    /// Tokenizes the values of the attribute with the tokenizer registered
+    /// under this name with
+    /// [Searcher::register_tokenizer](crate::Searcher::register_tokenizer),
+    /// instead of the one of its culture. The values are not inserted while
+    /// it is not registered.
+    pub fn tokenizer(mut self, name: &str) -> Self {
+        self.tokenizer = Some(name.into());
+        self
+    }
 }
@@ -0,0 +1,203 @@
+use crate::Direction;
+use roaring::RoaringBitmap;
+use std::io::{self, Read, Write};
+
+/// Computes the CRC-32 (IEEE) checksum of some bytes.
+pub(crate) fn crc32(bytes: &[u8]) -> u32 {
+    const TABLE: [u32; 256] = {
+        let mut table = [0; 256];
+        let mut i = 0;
+
+        while i < 256 {
+            let mut c = i as u32;
+            let mut k = 0;
+
+            while k < 8 {
+                c = if c & 1 == 1 {
+                    0xEDB8_8320 ^ (c >> 1)
+                } else {
+                    c >> 1
+                };
+                k += 1;
+            }
+
+            table[i] = c;
+            i += 1;
+        }
+
+        table
+    };
+
+    !bytes.iter().fold(!0, |crc, b| {
+        TABLE[((crc ^ *b as u32) & 0xFF) as usize] ^ (crc >> 8)
+    })
+}
+
+pub(crate) fn invalid_data<E>(error: E) -> io::Error
+where
+    E: Into<Box<dyn std::error::Error + Send + Sync>>,
+{
+    io::Error::new(io::ErrorKind::InvalidData, error)
+}
+
+pub(crate) fn read_bitmap<R: Read>(r: &mut R) -> io::Result<RoaringBitmap> {
+    RoaringBitmap::deserialize_from(r)
+}
+
+pub(crate) fn read_bool<R: Read>(r: &mut R) -> io::Result<bool> {
+    match read_u8(r)? {
+        0 => Ok(false),
+        1 => Ok(true),
+        v => Err(invalid_data(format!("invalid bool {v}"))),
+    }
+}
+
+pub(crate) fn read_direction<R: Read>(r: &mut R) -> io::Result<Direction> {
+    match read_u8(r)? {
+        0 => Ok(Direction::Forward),
+        1 => Ok(Direction::Backward),
+        v => Err(invalid_data(format!("invalid direction {v}"))),
+    }
+}
+
+/// Reads a frame written by [write_frame], `None` at the end of the reader.
+///
+/// A torn frame fails as an unexpected end of file, a corrupted one as invalid data.
+pub(crate) fn read_frame<R: Read>(r: &mut R) -> io::Result<Option<Vec<u8>>> {
+    let mut buf = [0; 4];
+    let mut read = 0;
+
+    while read < buf.len() {
+        match r.read(&mut buf[read..])? {
+            0 if read == 0 => return Ok(None),
+            0 => return Err(io::ErrorKind::UnexpectedEof.into()),
+            n => read += n,
+        }
+    }
+
+    let len = u32::from_le_bytes(buf) as usize;
+    let crc = read_u32(r)?;
+    let mut payload = Vec::new();
+
+    r.take(len as u64).read_to_end(&mut payload)?;
+
+    if payload.len() != len {
+        return Err(io::ErrorKind::UnexpectedEof.into());
+    }
+
+    if crc32(&payload) != crc {
+        return Err(invalid_data("checksum mismatch"));
+    }
+
+    Ok(Some(payload))
+}
+
+/// Reads and validates the magic bytes and the version of a format.
+pub(crate) fn read_header<R: Read>(r: &mut R, magic: &[u8; 4], version: u32) -> io::Result<()> {
+    let mut buf = [0; 4];
+
+    r.read_exact(&mut buf)?;
+
+    if &buf != magic {
+        return Err(invalid_data("invalid magic bytes"));
+    }
+
+    match read_u32(r)? {
+        v if v == version => Ok(()),
+        v => Err(invalid_data(format!("unsupported version {v}"))),
+    }
+}
+
+/// Reads a length prefix, used before collections.
+pub(crate) fn read_len<R: Read>(r: &mut R) -> io::Result<usize> {
+    Ok(read_u32(r)? as usize)
+}
+
+pub(crate) fn read_str<R: Read>(r: &mut R) -> io::Result<Box<str>> {
+    let len = read_len(r)?;
+    let mut buf = Vec::new();
+
+    r.take(len as u64).read_to_end(&mut buf)?;
+
+    if buf.len() != len {
+        return Err(io::ErrorKind::UnexpectedEof.into());
+    }
+
+    String::from_utf8(buf)
+        .map(String::into_boxed_str)
+        .map_err(invalid_data)
+}
+
+pub(crate) fn read_u8<R: Read>(r: &mut R) -> io::Result<u8> {
+    let mut buf = [0; 1];
+    r.read_exact(&mut buf)?;
+    Ok(buf[0])
+}
+
+pub(crate) fn read_u32<R: Read>(r: &mut R) -> io::Result<u32> {
+    let mut buf = [0; 4];
+    r.read_exact(&mut buf)?;
+    Ok(u32::from_le_bytes(buf))
+}
+
+pub(crate) fn read_u64<R: Read>(r: &mut R) -> io::Result<u64> {
+    let mut buf = [0; 8];
+    r.read_exact(&mut buf)?;
+    Ok(u64::from_le_bytes(buf))
+}
+
+pub(crate) fn write_bitmap<W: Write>(w: &mut W, bitmap: &RoaringBitmap) -> io::Result<()> {
+    bitmap.serialize_into(w)
+}
+
+pub(crate) fn write_bool<W: Write>(w: &mut W, v: bool) -> io::Result<()> {
+    write_u8(w, v as u8)
+}
+
+pub(crate) fn write_direction<W: Write>(w: &mut W, direction: Direction) -> io::Result<()> {
+    write_u8(
+        w,
+        match direction {
+            Direction::Forward => 0,
+            Direction::Backward => 1,
+        },
+    )
+}
+
+/// Writes a payload framed by its length and its CRC-32 checksum, in one write.
+pub(crate) fn write_frame<W: Write>(w: &mut W, payload: &[u8]) -> io::Result<()> {
+    let mut frame = Vec::with_capacity(payload.len() + 8);
+
+    write_len(&mut frame, payload.len())?;
+    write_u32(&mut frame, crc32(payload))?;
+    frame.extend_from_slice(payload);
+
+    w.write_all(&frame)
+}
+
+pub(crate) fn write_header<W: Write>(w: &mut W, magic: &[u8; 4], version: u32) -> io::Result<()> {
+    w.write_all(magic)?;
+    write_u32(w, version)
+}
+
+pub(crate) fn write_len<W: Write>(w: &mut W, len: usize) -> io::Result<()> {
+    let len = u32::try_from(len).map_err(invalid_data)?;
+    write_u32(w, len)
+}
+
+pub(crate) fn write_str<W: Write>(w: &mut W, s: &str) -> io::Result<()> {
+    write_len(w, s.len())?;
+    w.write_all(s.as_bytes())
+}
+
+pub(crate) fn write_u8<W: Write>(w: &mut W, v: u8) -> io::Result<()> {
+    w.write_all(&[v])
+}
+
+pub(crate) fn write_u32<W: Write>(w: &mut W, v: u32) -> io::Result<()> {
+    w.write_all(&v.to_le_bytes())
+}
+
+pub(crate) fn write_u64<W: Write>(w: &mut W, v: u64) -> io::Result<()> {
+    w.write_all(&v.to_le_bytes())
+}
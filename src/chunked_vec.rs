@@ -0,0 +1,322 @@
+#[cfg(feature = "rayon")]
+use rayon::prelude::*;
+use std::{iter::FusedIterator, ops, slice, sync::Arc};
+
+/// Maximum number of items of a chunk, a chunk growing over it is split.
+const CHUNK_LEN: usize = 256;
+
+/// A vector split in chunks shared between its clones, a chunk being copied
+/// when it is modified.
+///
+/// A clone costs one pointer per chunk and a write copies at most one chunk,
+/// the items after an insertion or a removal are not moved.
+#[derive(Clone, Debug)]
+pub(crate) struct ChunkedVec<T> {
+    chunks: Vec<Arc<Vec<T>>>,
+
+    /// the cumulative lengths of the chunks.
+    ends: Vec<usize>,
+}
+
+impl<T: Clone> ChunkedVec<T> {
+    pub(crate) const fn new() -> Self {
+        Self {
+            chunks: Vec::new(),
+            ends: Vec::new(),
+        }
+    }
+
+    /// Binary searches a sorted vector, as [slice::binary_search_by_key].
+    pub(crate) fn binary_search_by_key<'a, B, F>(&'a self, b: &B, mut f: F) -> Result<usize, usize>
+    where
+        B: Ord,
+        F: FnMut(&'a T) -> B,
+    {
+        let chunks: &'a [Arc<Vec<T>>] = &self.chunks;
+        let (mut c, mut high) = (0, chunks.len());
+
+        // the first chunk whose last item is not less than `b`.
+        while c < high {
+            let mid = c + (high - c) / 2;
+
+            match chunks[mid].last().is_some_and(|last| f(last) < *b) {
+                true => c = mid + 1,
+                false => high = mid,
+            }
+        }
+
+        let Some(chunk) = chunks.get(c) else {
+            return Err(self.len());
+        };
+
+        let start = self.chunk_start(c);
+
+        chunk
+            .binary_search_by_key(b, f)
+            .map(|i| start + i)
+            .map_err(|i| start + i)
+    }
+
+    fn chunk_start(&self, c: usize) -> usize {
+        c.checked_sub(1).map_or(0, |p| self.ends[p])
+    }
+
+    pub(crate) fn get(&self, index: usize) -> Option<&T> {
+        let (c, i) = self.locate(index)?;
+        Some(&self.chunks[c][i])
+    }
+
+    /// Gets an item to modify, copying its chunk if shared.
+    pub(crate) fn get_mut(&mut self, index: usize) -> Option<&mut T> {
+        let (c, i) = self.locate(index)?;
+        Some(&mut Arc::make_mut(&mut self.chunks[c])[i])
+    }
+
+    pub(crate) fn insert(&mut self, index: usize, value: T) {
+        assert!(index <= self.len(), "insertion index out of bounds");
+
+        if index == self.len() {
+            return self.push(value);
+        }
+
+        let (c, i) = self.locate(index).expect("index");
+        let chunk = Arc::make_mut(&mut self.chunks[c]);
+
+        chunk.insert(i, value);
+        self.ends[c..].iter_mut().for_each(|end| *end += 1);
+
+        if chunk.len() > CHUNK_LEN {
+            let tail = chunk.split_off(chunk.len() / 2);
+
+            self.ends.insert(c, self.ends[c] - tail.len());
+            self.chunks.insert(c + 1, Arc::new(tail));
+        }
+    }
+
+    pub(crate) fn iter(&self) -> Iter<'_, T> {
+        Iter {
+            chunks: self.chunks.iter(),
+            items: [].iter(),
+            len: self.len(),
+        }
+    }
+
+    pub(crate) fn is_empty(&self) -> bool {
+        self.chunks.is_empty()
+    }
+
+    pub(crate) fn last(&self) -> Option<&T> {
+        self.chunks.last()?.last()
+    }
+
+    pub(crate) fn len(&self) -> usize {
+        self.ends.last().copied().unwrap_or(0)
+    }
+
+    /// Iterates the items with their index in parallel, by chunk.
+    #[cfg(feature = "rayon")]
+    pub(crate) fn par_enumerate(&self) -> impl ParallelIterator<Item = (usize, &T)>
+    where
+        T: Send + Sync,
+    {
+        self.chunks
+            .par_iter()
+            .enumerate()
+            .flat_map_iter(move |(c, chunk)| {
+                let start = self.chunk_start(c);
+                chunk.iter().enumerate().map(move |(i, v)| (start + i, v))
+            })
+    }
+
+    /// Gets the chunk of an item and its index in the chunk.
+    fn locate(&self, index: usize) -> Option<(usize, usize)> {
+        if index >= self.len() {
+            return None;
+        }
+
+        let c = self.ends.partition_point(|end| *end <= index);
+        Some((c, index - self.chunk_start(c)))
+    }
+
+    pub(crate) fn push(&mut self, value: T) {
+        match self.chunks.last_mut() {
+            Some(chunk) if chunk.len() < CHUNK_LEN => {
+                Arc::make_mut(chunk).push(value);
+                *self.ends.last_mut().expect("end") += 1;
+            }
+            _ => {
+                self.chunks.push(Arc::new(vec![value]));
+                self.ends.push(self.len() + 1);
+            }
+        }
+    }
+
+    pub(crate) fn remove(&mut self, index: usize) -> T {
+        let (c, i) = self.locate(index).expect("removal index out of bounds");
+        let chunk = Arc::make_mut(&mut self.chunks[c]);
+        let value = chunk.remove(i);
+
+        self.ends[c..].iter_mut().for_each(|end| *end -= 1);
+
+        if chunk.is_empty() {
+            self.chunks.remove(c);
+            self.ends.remove(c);
+        }
+
+        value
+    }
+
+    /// Grows the vector to `len` items created by `f`, nothing is done when it
+    /// is already as long.
+    pub(crate) fn grow_with<F>(&mut self, len: usize, mut f: F)
+    where
+        F: FnMut() -> T,
+    {
+        while self.len() < len {
+            self.push(f());
+        }
+    }
+}
+
+impl<T: Clone> Default for ChunkedVec<T> {
+    #[inline]
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<T: Clone> Extend<T> for ChunkedVec<T> {
+    fn extend<I: IntoIterator<Item = T>>(&mut self, iter: I) {
+        iter.into_iter().for_each(|v| self.push(v));
+    }
+}
+
+impl<T: Clone> FromIterator<T> for ChunkedVec<T> {
+    fn from_iter<I: IntoIterator<Item = T>>(iter: I) -> Self {
+        let mut vec = Self::new();
+        vec.extend(iter);
+        vec
+    }
+}
+
+impl<T: Clone> ops::Index<usize> for ChunkedVec<T> {
+    type Output = T;
+
+    fn index(&self, index: usize) -> &T {
+        self.get(index).expect("index out of bounds")
+    }
+}
+
+impl<T: Clone> IntoIterator for ChunkedVec<T> {
+    type Item = T;
+    type IntoIter = IntoIter<T>;
+
+    /// Takes the items, the chunks still shared being copied.
+    fn into_iter(self) -> IntoIter<T> {
+        IntoIter {
+            chunks: self.chunks.into_iter(),
+            items: Vec::new().into_iter(),
+            len: self.ends.last().copied().unwrap_or(0),
+        }
+    }
+}
+
+impl<'a, T: Clone> IntoIterator for &'a ChunkedVec<T> {
+    type Item = &'a T;
+    type IntoIter = Iter<'a, T>;
+
+    fn into_iter(self) -> Iter<'a, T> {
+        self.iter()
+    }
+}
+
+#[derive(Clone)]
+pub(crate) struct Iter<'a, T> {
+    chunks: slice::Iter<'a, Arc<Vec<T>>>,
+    items: slice::Iter<'a, T>,
+    len: usize,
+}
+
+impl<'a, T> Iterator for Iter<'a, T> {
+    type Item = &'a T;
+
+    fn next(&mut self) -> Option<&'a T> {
+        loop {
+            if let Some(item) = self.items.next() {
+                self.len -= 1;
+                return Some(item);
+            }
+
+            self.items = self.chunks.next()?.iter();
+        }
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        (self.len, Some(self.len))
+    }
+}
+
+impl<T> ExactSizeIterator for Iter<'_, T> {}
+
+impl<T> FusedIterator for Iter<'_, T> {}
+
+pub(crate) struct IntoIter<T> {
+    chunks: std::vec::IntoIter<Arc<Vec<T>>>,
+    items: std::vec::IntoIter<T>,
+    len: usize,
+}
+
+impl<T: Clone> Iterator for IntoIter<T> {
+    type Item = T;
+
+    fn next(&mut self) -> Option<T> {
+        loop {
+            if let Some(item) = self.items.next() {
+                self.len -= 1;
+                return Some(item);
+            }
+
+            self.items = Arc::unwrap_or_clone(self.chunks.next()?).into_iter();
+        }
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        (self.len, Some(self.len))
+    }
+}
+
+impl<T: Clone> ExactSizeIterator for IntoIter<T> {}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn insert_remove_shared() {
+        let mut vec = (0..1000).map(|i| i * 2).collect::<ChunkedVec<u32>>();
+        let copy = vec.clone();
+
+        for v in (0..600).map(|i| i * 2 + 1) {
+            let index = vec.binary_search_by_key(&v, |v| *v).unwrap_err();
+            vec.insert(index, v);
+        }
+
+        assert!(vec
+            .iter()
+            .copied()
+            .eq((0..1200).chain((600..1000).map(|i| i * 2))));
+        assert_eq!(vec.binary_search_by_key(&1197, |v| *v), Ok(1197));
+        assert_eq!(vec.binary_search_by_key(&5000, |v| *v), Err(1600));
+
+        vec.remove(0);
+        *vec.get_mut(10).unwrap() = 0;
+
+        assert_eq!(vec.len(), 1599);
+        assert_eq!(vec.iter().len(), 1599);
+        assert_eq!(vec[9], 10);
+        assert_eq!(vec[10], 0);
+
+        assert!(copy.iter().copied().eq((0..1000).map(|i| i * 2)));
+        assert!(copy.into_iter().eq((0..1000).map(|i| i * 2)));
+    }
+}
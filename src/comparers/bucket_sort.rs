@@ -0,0 +1,158 @@
+use super::{Side, WorkingSet};
+use crate::{DocId, SearchResults};
+use std::{mem::take, ops::Range};
+
+/// Sorts the docs of the results by relevance, grouping them into buckets of
+/// docs of equal relevance criterion by criterion.
+///
+/// Each doc is scored once per criterion, and only the buckets overlapping the
+/// requested range are refined, the others are left unsorted. Docs equal for
+/// all the criteria are ordered by id, giving the same order as
+/// [compare](super::compare).
+pub(crate) struct BucketSort<'a, 'b> {
+    buckets: Vec<Bucket>,
+    ids: Vec<DocId>,
+    results: &'b SearchResults<'a>,
+    set: WorkingSet,
+    temp_set: WorkingSet,
+}
+
+impl<'a, 'b> BucketSort<'a, 'b> {
+    pub(crate) fn new(results: &'b SearchResults<'a>) -> Self {
+        let ids = results.iter().collect::<Vec<_>>();
+
+        Self {
+            buckets: vec![Bucket {
+                criterion: 0,
+                end: ids.len(),
+            }],
+            ids,
+            results,
+            set: WorkingSet::new(),
+            temp_set: WorkingSet::new(),
+        }
+    }
+
+    /// The number of criteria, a match distance and a proximity by priority.
+    fn criterion_count(&self) -> usize {
+        self.results.attrs_priorities.len() * 2
+    }
+
+    #[inline]
+    pub(crate) fn ids(&self) -> &[DocId] {
+        &self.ids
+    }
+
+    /// Sorts the bucket starting at `start` into `out` if it overlaps `range`.
+    fn refine(
+        &mut self,
+        start: usize,
+        bucket: Bucket,
+        range: &Range<usize>,
+        out: &mut Vec<Bucket>,
+    ) {
+        let done = bucket.criterion == SORTED
+            || bucket.end <= range.start
+            || start >= range.end
+            || bucket.end - start <= 1;
+
+        if done {
+            out.push(bucket);
+            return;
+        }
+
+        if bucket.criterion == self.criterion_count() {
+            self.ids[start..bucket.end].sort_unstable();
+            out.push(Bucket {
+                criterion: SORTED,
+                end: bucket.end,
+            });
+            return;
+        }
+
+        let results = self.results;
+        let (_, attrs) = &results.attrs_priorities[bucket.criterion / 2];
+        let ids = &mut self.ids[start..bucket.end];
+        let mut ends = Vec::new();
+
+        if bucket.criterion % 2 == 1 {
+            let mut scored = ids
+                .iter()
+                .map(|&id| {
+                    let mut side = Side::new(id, results, &mut self.set);
+                    (side.proximity_seq(attrs, &mut self.temp_set).clone(), id)
+                })
+                .collect::<Vec<_>>();
+
+            sort_into(&mut scored, ids, &mut ends);
+        } else {
+            let mut scored = ids
+                .iter()
+                .map(|&id| {
+                    let mut side = Side::new(id, results, &mut self.set);
+                    (side.match_distance(attrs, &mut self.temp_set).clone(), id)
+                })
+                .collect::<Vec<_>>();
+
+            sort_into(&mut scored, ids, &mut ends);
+        }
+
+        let mut sub_start = start;
+
+        for end in ends {
+            let sub = Bucket {
+                criterion: bucket.criterion + 1,
+                end: start + end,
+            };
+
+            self.refine(sub_start, sub, range, out);
+            sub_start = sub.end;
+        }
+    }
+
+    /// Sorts the ids in `range`, the ids outside being in their final bucket
+    /// but not necessarily sorted.
+    pub(crate) fn sort_range(&mut self, range: Range<usize>) -> &[DocId] {
+        let range = range.start.min(self.ids.len())..range.end.min(self.ids.len());
+
+        if range.start < range.end {
+            let buckets = take(&mut self.buckets);
+            let mut out = Vec::with_capacity(buckets.len());
+            let mut start = 0;
+
+            for bucket in buckets {
+                self.refine(start, bucket, &range, &mut out);
+                start = bucket.end;
+            }
+
+            self.buckets = out;
+        }
+
+        &self.ids[range]
+    }
+}
+
+/// A run of docs equal for the criteria before `criterion`, ending at `end`,
+/// starting at the end of the previous bucket.
+#[derive(Clone, Copy, Debug)]
+struct Bucket {
+    criterion: usize,
+    end: usize,
+}
+
+/// The criterion of a bucket whose docs are in their final order.
+const SORTED: usize = usize::MAX;
+
+/// Sorts the scored ids into `ids`, pushing the end of each run of equal
+/// scores into `ends`.
+fn sort_into<S: Ord>(scored: &mut [(S, DocId)], ids: &mut [DocId], ends: &mut Vec<usize>) {
+    scored.sort_by(|l, r| l.0.cmp(&r.0));
+
+    for (i, (score, id)) in scored.iter().enumerate() {
+        ids[i] = *id;
+
+        if !matches!(scored.get(i + 1), Some(next) if next.0.cmp(score).is_eq()) {
+            ends.push(i + 1);
+        }
+    }
+}
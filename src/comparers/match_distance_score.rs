@@ -1,7 +1,8 @@
+use super::rank_key::{END, LEVEL};
 use crate::{index_to_query::IndexToQuery, MatchDistance};
 use std::cmp::{min, Ordering};
 
-#[derive(Debug)]
+#[derive(Clone, Debug)]
 pub(super) struct MatchDistanceScore(Vec<Rec>);
 
 impl MatchDistanceScore {
@@ -33,16 +34,32 @@ impl MatchDistanceScore {
     }
 
     /// Add a list of words and compute the match distance score.
-    pub(super) fn update(&mut self, index: &IndexToQuery, words: &[*const str]) {
+    pub(super) fn update<I>(&mut self, index: &IndexToQuery, words: I)
+    where
+        I: IntoIterator<Item = *const str>,
+    {
         self.clear();
 
         for word in words {
-            self.add_word(index, *word);
+            self.add_word(index, word);
         }
 
         self.0.retain(|t| t.distance.is_some());
         self.0.sort_unstable();
     }
+
+    /// Writes the records so that the bytes compare as the scores.
+    pub(super) fn write_key(&self, out: &mut Vec<u8>) {
+        for rec in &self.0 {
+            if let Some(distance) = rec.distance {
+                out.push(LEVEL);
+                out.push(distance.0);
+                out.extend_from_slice(&(rec.index as u64).to_be_bytes());
+            }
+        }
+
+        out.push(END);
+    }
 }
 
 impl Eq for MatchDistanceScore {}
@@ -1,9 +1,13 @@
+mod bucket_sort;
 mod match_distance_score;
 mod proximity_seq_score;
+mod rank_key;
 
-use crate::{Direction, DocId, IndexResults, SearchResults};
+use crate::{tokenizer::positions, Direction, DocId, IndexResults, SearchResults};
+pub(crate) use bucket_sort::BucketSort;
 use match_distance_score::MatchDistanceScore;
 use proximity_seq_score::ProximitySeqScore;
+pub use rank_key::RankKey;
 use std::{
     cell::Cell,
     cmp::Ordering,
@@ -133,7 +137,10 @@ impl WorkingSet {
         let words = results.index.get_doc_attribute_words(id, attr_index);
 
         if !words.is_empty() {
-            set.proximity_seq.update(&results.index_to_query, words);
+            let ranges = results.index.get_doc_attribute_ranges(id, attr_index);
+
+            set.proximity_seq
+                .update(&results.index_to_query, words.zip(positions(ranges)));
 
             if self.proximity_seq > set.proximity_seq {
                 swap(&mut self.proximity_seq, &mut set.proximity_seq);
@@ -152,7 +159,7 @@ struct Side<'a> {
 impl<'a> Side<'a> {
     fn new(doc_id: DocId, results: &'a SearchResults<'a>, set: &'a mut WorkingSet) -> Self {
         Self {
-            attrs_priorities: results.searcher.attrs_priorities(results.culture),
+            attrs_priorities: results.attrs_priorities,
             doc_id,
             results,
             set,
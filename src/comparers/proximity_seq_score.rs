@@ -1,7 +1,7 @@
 use crate::IndexToQuery;
 use std::cmp::{max, min, Ordering};
 
-#[derive(Debug)]
+#[derive(Clone, Debug)]
 pub(super) struct ProximitySeqScore {
     count: usize,
     locations: Vec<Option<usize>>,
@@ -67,14 +67,26 @@ impl ProximitySeqScore {
         }
     }
 
-    pub(super) fn update(&mut self, index: &IndexToQuery, words: &[*const str]) {
+    /// Updates the score with the words of an attribute and their position.
+    pub(super) fn update<I>(&mut self, index: &IndexToQuery, words: I)
+    where
+        I: IntoIterator<Item = (*const str, usize)>,
+    {
         self.clear();
 
-        for (word_location, &word) in words.iter().enumerate() {
+        for (word, word_location) in words {
             self.add_word(index, word, word_location);
         }
     }
 
+    /// Writes the score so that the bytes compare as the scores.
+    pub(super) fn write_key(&self, out: &mut Vec<u8>) {
+        out.extend_from_slice(&(!(self.count as u64)).to_be_bytes());
+        out.extend_from_slice(&(self.proximity as u64).to_be_bytes());
+        out.extend_from_slice(&(!(self.seq as u64)).to_be_bytes());
+        out.extend_from_slice(&(self.start_at as u64).to_be_bytes());
+    }
+
     fn update_proximity_seq(&mut self) {
         if self.count > 1 {
             let (prox, seq, start) = self.locations.iter().filter_map(|l| *l).fold(
@@ -0,0 +1,44 @@
+use super::{Side, WorkingSet};
+use crate::{DocId, SearchResults};
+
+/// A compact key of the relevance of a doc, keys compare as their docs
+/// compare with [compare](super::compare).
+///
+/// Keys are computed once per doc, so they can be cached, sorted in parallel or
+/// merged with the keys of results from other searchers.
+#[derive(Clone, Debug, Eq, Hash, Ord, PartialEq, PartialOrd)]
+pub struct RankKey(Box<[u8]>);
+
+impl RankKey {
+    /// Computes the key of a doc, each priority level being the match distance
+    /// then the proximity of the doc in the attributes of that priority.
+    pub(crate) fn new(id: DocId, results: &SearchResults) -> Self {
+        let mut set = WorkingSet::new();
+        let mut temp_set = WorkingSet::new();
+        let mut side = Side::new(id, results, &mut set);
+        let mut out = Vec::new();
+
+        for (_priority, attrs) in results.attrs_priorities {
+            out.push(LEVEL);
+            side.match_distance(attrs, &mut temp_set)
+                .write_key(&mut out);
+            side.proximity_seq(attrs, &mut temp_set).write_key(&mut out);
+        }
+
+        // A key with less priority levels sorts after.
+        out.push(END);
+
+        Self(out.into_boxed_slice())
+    }
+
+    #[inline]
+    pub fn as_bytes(&self) -> &[u8] {
+        &self.0
+    }
+}
+
+/// Starts an item of a sequence, sorting before the end of the sequence.
+pub(super) const LEVEL: u8 = 0;
+
+/// Ends a sequence, sorting a sequence after the longer ones it prefixes.
+pub(super) const END: u8 = 1;
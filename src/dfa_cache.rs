@@ -0,0 +1,116 @@
+use fxhash::FxBuildHasher;
+use indexmap::IndexMap;
+use levenshtein_automata::{LevenshteinAutomatonBuilder, DFA};
+use once_cell::sync::Lazy;
+use std::sync::{Arc, Mutex, PoisonError};
+
+/// The number of DFAs kept by the shared cache of each builder.
+const CAPACITY: usize = 256;
+
+/// The builders by max distance - 1, then with transpositions, built on first
+/// use as building one is much more expensive than building a DFA from it.
+static BUILDERS: [Lazy<LevenshteinAutomatonBuilder>; 6] = [
+    Lazy::new(|| LevenshteinAutomatonBuilder::new(1, false)),
+    Lazy::new(|| LevenshteinAutomatonBuilder::new(2, false)),
+    Lazy::new(|| LevenshteinAutomatonBuilder::new(3, false)),
+    Lazy::new(|| LevenshteinAutomatonBuilder::new(1, true)),
+    Lazy::new(|| LevenshteinAutomatonBuilder::new(2, true)),
+    Lazy::new(|| LevenshteinAutomatonBuilder::new(3, true)),
+];
+
+static CACHES: [Lazy<Mutex<DfaCache>>; 6] = [
+    Lazy::new(|| Mutex::new(DfaCache::new(CAPACITY))),
+    Lazy::new(|| Mutex::new(DfaCache::new(CAPACITY))),
+    Lazy::new(|| Mutex::new(DfaCache::new(CAPACITY))),
+    Lazy::new(|| Mutex::new(DfaCache::new(CAPACITY))),
+    Lazy::new(|| Mutex::new(DfaCache::new(CAPACITY))),
+    Lazy::new(|| Mutex::new(DfaCache::new(CAPACITY))),
+];
+
+/// Gets the prefix DFA of a word from the shared cache, building it with the
+/// shared builder on a miss.
+///
+/// The distance must be between 1 and 3.
+pub(crate) fn prefix_dfa(word: &str, distance: u8, transpositions: bool) -> Arc<DFA> {
+    let index = transpositions as usize * 3 + distance as usize - 1;
+    let cache = || CACHES[index].lock().unwrap_or_else(PoisonError::into_inner);
+
+    if let Some(dfa) = cache().get(word) {
+        return dfa;
+    }
+
+    // built without holding the lock, another query may build it too.
+    let dfa = Arc::new(BUILDERS[index].build_prefix_dfa(word));
+
+    cache().insert(word, dfa.clone());
+    dfa
+}
+
+/// The DFAs of the last used words, the least recently used one being evicted
+/// when full.
+struct DfaCache {
+    capacity: usize,
+
+    /// From the least to the most recently used.
+    dfas: IndexMap<Box<str>, Arc<DFA>, FxBuildHasher>,
+}
+
+impl DfaCache {
+    fn new(capacity: usize) -> Self {
+        Self {
+            capacity,
+            dfas: IndexMap::default(),
+        }
+    }
+
+    fn get(&mut self, word: &str) -> Option<Arc<DFA>> {
+        let index = self.dfas.get_index_of(word)?;
+        self.dfas.move_index(index, self.dfas.len() - 1);
+        self.dfas.last().map(|(_, dfa)| dfa.clone())
+    }
+
+    fn insert(&mut self, word: &str, dfa: Arc<DFA>) {
+        if let Some(index) = self.dfas.get_index_of(word) {
+            self.dfas.move_index(index, self.dfas.len() - 1);
+            return;
+        }
+
+        if self.dfas.len() == self.capacity {
+            self.dfas.shift_remove_index(0);
+        }
+
+        self.dfas.insert(word.into(), dfa);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn least_recently_used_evicted() {
+        let mut cache = DfaCache::new(2);
+        let dfa = |word| Arc::new(BUILDERS[0].build_prefix_dfa(word));
+
+        cache.insert("bal", dfa("bal"));
+        cache.insert("bala", dfa("bala"));
+
+        assert!(cache.get("bal").is_some());
+
+        cache.insert("balan", dfa("balan"));
+
+        assert!(cache.get("bala").is_none());
+        assert!(cache.get("bal").is_some());
+        assert!(cache.get("balan").is_some());
+    }
+
+    #[test]
+    fn shared_dfa() {
+        let a = prefix_dfa("typeahead", 3, true);
+        let b = prefix_dfa("typeahead", 3, true);
+        let c = prefix_dfa("typeahead", 3, false);
+
+        assert!(Arc::ptr_eq(&a, &b));
+        assert!(!Arc::ptr_eq(&a, &c));
+    }
+}
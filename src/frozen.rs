@@ -0,0 +1,1080 @@
+use crate::{
+    binary::{invalid_data, read_header, read_len, write_bitmap, write_len, write_u32},
+    index::{query_word_rows, trigram_match},
+    match_entry::MatchDocs,
+    searcher::{culture_attrs_priorities, query_indexes, read_attrs, AttrsPriorities},
+    stop_words::StopWords,
+    stored_values::StoredRef,
+    trigram_index::{trigrams, unique_trigrams, Trigram},
+    word_index::WordRows,
+    Analyzers, AttrMap, Direction, DocId, FuzzyPolicy, Index, IndexRef, MatchEntry, Normalizer,
+    SearchQuery, SearchResults, StoredValues, Tokenizer, WordIndex, WordQuery,
+};
+use roaring::RoaringBitmap;
+use std::{
+    borrow::Cow,
+    io::{self, Write},
+    ops::{Range, RangeInclusive},
+    str::{from_utf8, from_utf8_unchecked},
+    sync::Arc,
+};
+
+pub(crate) const MAGIC: &[u8; 4] = b"SRCF";
+pub(crate) const VERSION: u32 = 11;
+
+/// A read only searcher querying in place the bytes written by
+/// [crate::Searcher::save_frozen_to], usually a memory mapped file.
+///
+/// Opening only reads the attributes and locates the sections of the indexes,
+/// the sorted words, their trigrams, the words of the docs and the stored
+/// values are read in place when a query needs them.
+///
+/// The posting bitmaps are kept in the portable serialization of roaring and
+/// read in place, only the containers added to or intersected with the docs
+/// of a query are copied, see the `frozen_queries` example comparing it with
+/// a [crate::Searcher].
+///
+/// The tokenizers and normalizers are not saved, the ones of the searcher
+/// that wrote the bytes must be registered again to split the queries as its
+/// values were.
+pub struct FrozenSearcher<B> {
+    analyzers: Analyzers,
+    attrs: AttrMap,
+    attrs_priorities: AttrsPriorities,
+    backward: FrozenLayout,
+    bytes: B,
+    forward: FrozenLayout,
+    stored: StoredLayout,
+}
+
+impl<B: AsRef<[u8]>> FrozenSearcher<B> {
+    pub fn open(bytes: B) -> io::Result<Self> {
+        let data = bytes.as_ref();
+        let mut r = data;
+
+        read_header(&mut r, MAGIC, VERSION)?;
+
+        let attrs = read_attrs(&mut r)?;
+        let backward = FrozenLayout::read(data, &mut r, Direction::Backward)?;
+        let forward = FrozenLayout::read(data, &mut r, Direction::Forward)?;
+        let stored = StoredLayout::read(data, &mut r)?;
+
+        Ok(Self {
+            analyzers: Analyzers::default(),
+            attrs,
+            attrs_priorities: AttrsPriorities::new(),
+            backward,
+            bytes,
+            forward,
+            stored,
+        })
+    }
+
+    /// Gets the raw value of a stored attribute of a doc, as it was inserted.
+    pub fn get_doc_attr_value(&self, doc_id: DocId, name: &str) -> Option<&str> {
+        let position = self.attrs.get_index_of(name)?;
+        self.stored().get(doc_id, position)
+    }
+
+    /// Gets the words inside a doc attribute as they are indexed.
+    pub fn get_doc_attr_words<'a>(
+        &'a self,
+        doc_id: DocId,
+        name: &str,
+    ) -> impl Iterator<Item = &'a str> {
+        let words = match self.attrs.get(name) {
+            Some(a) => self
+                .index(a.direction)
+                .get_doc_attribute_words(doc_id, a.index),
+            None => FrozenDocAttrWords {
+                index: self.index(Direction::Forward),
+                words: 0..0,
+            },
+        };
+
+        words.map(|w| unsafe { &*w })
+    }
+
+    fn index(&self, direction: Direction) -> FrozenIndex<'_> {
+        FrozenIndex {
+            bytes: self.bytes.as_ref(),
+            layout: match direction {
+                Direction::Backward => &self.backward,
+                Direction::Forward => &self.forward,
+            },
+        }
+    }
+
+    /// Parses a query with the tokenizer and normalizer of its culture, as
+    /// [crate::Searcher::parse_query].
+    pub fn parse_query(&self, culture: u8, s: &str) -> SearchQuery {
+        let analyzer = self.analyzers.culture(culture);
+        SearchQuery::parse_with(culture, s, analyzer.tokenizer, analyzer.normalizer)
+    }
+
+    pub fn query<'a>(&'a self, query: &SearchQuery) -> SearchResults<'a> {
+        query_indexes(
+            &self.attrs,
+            culture_attrs_priorities(&self.attrs, &self.attrs_priorities, query.culture),
+            IndexRef::Frozen(self.index(Direction::Backward)),
+            IndexRef::Frozen(self.index(Direction::Forward)),
+            Some(StoredRef::Frozen(self.stored())),
+            &self.analyzers,
+            query,
+        )
+    }
+
+    /// Registers a normalizer the attributes can refer to by name, as
+    /// [crate::Searcher::register_normalizer].
+    pub fn register_normalizer(&mut self, name: &str, normalizer: Arc<dyn Normalizer>) {
+        self.analyzers.insert_normalizer(name, normalizer);
+    }
+
+    /// Registers a tokenizer the attributes can refer to by name, as
+    /// [crate::Searcher::register_tokenizer].
+    pub fn register_tokenizer(&mut self, name: &str, tokenizer: Arc<dyn Tokenizer>) {
+        self.analyzers.insert_tokenizer(name, tokenizer);
+    }
+
+    /// Sets the normalizer of a culture, as [crate::Searcher::set_culture_normalizer].
+    pub fn set_culture_normalizer(&mut self, culture: u8, normalizer: Arc<dyn Normalizer>) {
+        self.analyzers.set_culture_normalizer(culture, normalizer);
+    }
+
+    /// Sets the tokenizer of a culture, as [crate::Searcher::set_culture_tokenizer].
+    pub fn set_culture_tokenizer(&mut self, culture: u8, tokenizer: Arc<dyn Tokenizer>) {
+        self.analyzers.set_culture_tokenizer(culture, tokenizer);
+    }
+
+    fn stored(&self) -> FrozenStored<'_> {
+        FrozenStored {
+            bytes: self.bytes.as_ref(),
+            layout: &self.stored,
+        }
+    }
+}
+
+struct CultureLayout {
+    stop_rows: RowsLayout,
+    words: RowsLayout,
+}
+
+struct RowsLayout {
+    bitmaps: Range<usize>,
+
+    /// pairs of word id and end offset of the bitmap.
+    rows: Range<usize>,
+}
+
+impl RowsLayout {
+    fn read(data: &[u8], r: &mut &[u8]) -> io::Result<Self> {
+        let rows = section(data, r, 8)?;
+        let bitmaps = section(data, r, 1)?;
+
+        Ok(Self { bitmaps, rows })
+    }
+}
+
+/// Location of the sections of an index inside the bytes.
+///
+/// The sections of variable sized items are preceded by the cumulative end
+/// offsets of the items.
+struct FrozenLayout {
+    attr_ends: Range<usize>,
+    direction: Direction,
+    doc_ends: Range<usize>,
+
+    /// the byte ranges of the words of each doc attribute, as pairs of little
+    /// endian u32.
+    range_ends: Range<usize>,
+    ranges: Range<usize>,
+
+    /// the word indexes and the stop rows of each group of attributes, by
+    /// culture.
+    groups: Vec<Vec<CultureLayout>>,
+
+    /// the stop words of each group, normalized as its words.
+    stop_words: Vec<StopWords>,
+
+    /// the vocabulary ids of the words of each trigram.
+    trigram_ends: Range<usize>,
+    trigram_word_ids: Range<usize>,
+
+    /// the sorted trigrams, as little endian u32.
+    trigrams: Range<usize>,
+    word_ends: Range<usize>,
+    word_ids: Range<usize>,
+    words: Range<usize>,
+}
+
+impl FrozenLayout {
+    fn read(data: &[u8], r: &mut &[u8], direction: Direction) -> io::Result<Self> {
+        let word_ends = section(data, r, 4)?;
+        let words = section(data, r, 1)?;
+
+        // validated once here, words are then sliced without checking.
+        from_utf8(&data[words.clone()]).map_err(invalid_data)?;
+
+        let doc_ends = section(data, r, 4)?;
+        let attr_ends = section(data, r, 4)?;
+        let word_ids = section(data, r, 4)?;
+        let range_ends = section(data, r, 4)?;
+        let ranges = section(data, r, 8)?;
+        let trigrams = section(data, r, 4)?;
+        let trigram_ends = section(data, r, 4)?;
+        let trigram_word_ids = section(data, r, 4)?;
+        let mut groups = Vec::new();
+        let mut stop_words = Vec::new();
+
+        for _ in 0..read_len(r)? {
+            let mut cultures = Vec::new();
+
+            for _ in 0..read_len(r)? {
+                let words = RowsLayout::read(data, r)?;
+                let stop_rows = RowsLayout::read(data, r)?;
+
+                cultures.push(CultureLayout { stop_rows, words });
+            }
+
+            groups.push(cultures);
+            stop_words.push(StopWords::read_from(r)?);
+        }
+
+        Ok(Self {
+            attr_ends,
+            direction,
+            doc_ends,
+            groups,
+            range_ends,
+            ranges,
+            stop_words,
+            trigram_ends,
+            trigram_word_ids,
+            trigrams,
+            word_ends,
+            word_ids,
+            words,
+        })
+    }
+}
+
+/// Location of the stored values inside the bytes, the values of each doc by
+/// the position of their attribute, an empty value being not stored.
+struct StoredLayout {
+    doc_ends: Range<usize>,
+    value_ends: Range<usize>,
+    values: Range<usize>,
+}
+
+impl StoredLayout {
+    fn read(data: &[u8], r: &mut &[u8]) -> io::Result<Self> {
+        let doc_ends = section(data, r, 4)?;
+        let value_ends = section(data, r, 4)?;
+        let values = section(data, r, 1)?;
+
+        // validated once here, values are then sliced without checking.
+        from_utf8(&data[values.clone()]).map_err(invalid_data)?;
+
+        Ok(Self {
+            doc_ends,
+            value_ends,
+            values,
+        })
+    }
+}
+
+#[derive(Clone, Copy)]
+pub(crate) struct FrozenStored<'a> {
+    bytes: &'a [u8],
+    layout: &'a StoredLayout,
+}
+
+impl<'a> FrozenStored<'a> {
+    pub(crate) fn get(self, doc_id: DocId, attr_position: usize) -> Option<&'a str> {
+        let layout = self.layout;
+        let values = range_at(self.bytes, &layout.doc_ends, doc_id.index())?;
+
+        if attr_position >= values.len() {
+            return None;
+        }
+
+        let range = range_at(self.bytes, &layout.value_ends, values.start + attr_position)?;
+        let values = unsafe { from_utf8_unchecked(&self.bytes[layout.values.clone()]) };
+
+        values.get(range).filter(|v| !v.is_empty())
+    }
+}
+
+pub(crate) struct FrozenDocAttrRanges<'a> {
+    index: FrozenIndex<'a>,
+    ranges: Range<usize>,
+}
+
+impl<'a> Iterator for FrozenDocAttrRanges<'a> {
+    type Item = Range<u32>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        let index = self.ranges.next()?;
+        let ranges = &self.index.layout.ranges;
+        let start = self.index.u32_at(ranges, index * 2)?;
+        let end = self.index.u32_at(ranges, index * 2 + 1)?;
+
+        Some(start as u32..end as u32)
+    }
+}
+
+#[derive(Clone)]
+pub(crate) struct FrozenDocAttrWords<'a> {
+    index: FrozenIndex<'a>,
+    words: Range<usize>,
+}
+
+impl<'a> Iterator for FrozenDocAttrWords<'a> {
+    type Item = *const str;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        let index = self.words.next()?;
+        let id = self.index.u32_at(&self.index.layout.word_ids, index)?;
+
+        Some(self.index.word(id))
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        self.words.size_hint()
+    }
+}
+
+#[derive(Clone, Copy)]
+pub(crate) struct FrozenIndex<'a> {
+    bytes: &'a [u8],
+    layout: &'a FrozenLayout,
+}
+
+impl<'a> FrozenIndex<'a> {
+    /// Gets the position of a doc attribute among the attributes of all the docs.
+    fn doc_attr_position(self, id: DocId, attr_index: usize) -> Option<usize> {
+        self.range_at(&self.layout.doc_ends, id.index())
+            .filter(|attrs| attr_index < attrs.len())
+            .map(|attrs| attrs.start + attr_index)
+    }
+
+    pub(crate) fn get_doc_attribute_ranges(
+        self,
+        id: DocId,
+        attr_index: usize,
+    ) -> FrozenDocAttrRanges<'a> {
+        let ranges = self
+            .doc_attr_position(id, attr_index)
+            .and_then(|position| self.range_at(&self.layout.range_ends, position))
+            .unwrap_or_default();
+
+        FrozenDocAttrRanges {
+            index: self,
+            ranges,
+        }
+    }
+
+    pub(crate) fn get_doc_attribute_words(
+        self,
+        id: DocId,
+        attr_index: usize,
+    ) -> FrozenDocAttrWords<'a> {
+        let words = self
+            .doc_attr_position(id, attr_index)
+            .and_then(|position| self.range_at(&self.layout.attr_ends, position))
+            .unwrap_or_default();
+
+        FrozenDocAttrWords { index: self, words }
+    }
+
+    pub(crate) fn query(
+        self,
+        q: &WordQuery,
+        group: usize,
+        culture: u8,
+        policy: &FuzzyPolicy,
+        out: &mut Vec<MatchEntry<'a>>,
+    ) {
+        let Some(cultures) = self.layout.groups.get(group) else {
+            return;
+        };
+
+        let direction = self.layout.direction;
+
+        if let Some(culture) = cultures.get(culture as usize).or_else(|| cultures.first()) {
+            let rows = FrozenRows {
+                index: self,
+                layout: &culture.words,
+            };
+
+            // only the candidate words of the trigrams are verified.
+            let candidates = trigram_match(direction, q)
+                .and_then(|(s, is_match)| Some((self.containing_candidates(s)?, is_match)));
+
+            match candidates {
+                Some((candidates, is_match)) => rows.query_candidates(
+                    candidates.map(|id| self.word(id)),
+                    q.directional_word(direction),
+                    is_match,
+                    out,
+                ),
+                None => query_word_rows(direction, rows, q, policy, out),
+            }
+        }
+    }
+
+    /// Checks if a word of a query is a stop word of a culture in a group, as
+    /// [Index::is_stop_word].
+    pub(crate) fn is_stop_word(self, group: usize, culture: u8, word: &str) -> bool {
+        let layout = self.layout;
+
+        layout.stop_words.get(group).is_some_and(|stop_words| {
+            let culture = match (culture as usize) < layout.groups[group].len() {
+                true => culture as usize,
+                false => 0,
+            };
+
+            stop_words.contains(culture, word)
+        })
+    }
+
+    /// Finds the docs of a stop word of a culture in a group, not in its word
+    /// index.
+    pub(crate) fn query_stop_word(
+        self,
+        q: &WordQuery,
+        group: usize,
+        culture: u8,
+        out: &mut Vec<MatchEntry<'a>>,
+    ) {
+        let Some(cultures) = self.layout.groups.get(group) else {
+            return;
+        };
+
+        if let Some(culture) = cultures.get(culture as usize).or_else(|| cultures.first()) {
+            let rows = FrozenRows {
+                index: self,
+                layout: &culture.stop_rows,
+            };
+
+            rows.eq(q.directional_word(self.layout.direction), out);
+        }
+    }
+
+    /// Gets the vocabulary ids of the words sharing the rarest trigram of `s`,
+    /// `None` when `s` is too short to have a trigram.
+    fn containing_candidates(self, s: &str) -> Option<impl Iterator<Item = usize> + 'a> {
+        let layout = self.layout;
+        let trigram_count = layout.trigrams.len() / 4;
+        let mut best = None::<Range<usize>>;
+
+        for t in trigrams(s) {
+            let key = trigram_key(t) as usize;
+            let index = partition_point(trigram_count, |i| {
+                self.u32_at(&layout.trigrams, i) < Some(key)
+            });
+
+            let ids = match self.u32_at(&layout.trigrams, index) == Some(key) {
+                true => self
+                    .range_at(&layout.trigram_ends, index)
+                    .unwrap_or_default(),
+                false => 0..0,
+            };
+
+            if best.as_ref().is_none_or(|b| ids.len() < b.len()) {
+                best = Some(ids);
+            }
+        }
+
+        let ids = best?;
+        Some(ids.filter_map(move |i| self.u32_at(&layout.trigram_word_ids, i)))
+    }
+
+    /// Gets the range of an item from a section of cumulative end offsets.
+    fn range_at(self, ends: &Range<usize>, index: usize) -> Option<Range<usize>> {
+        range_at(self.bytes, ends, index)
+    }
+
+    fn u32_at(self, section: &Range<usize>, index: usize) -> Option<usize> {
+        u32_at(self.bytes, section, index)
+    }
+
+    fn word(self, id: usize) -> &'a str {
+        let words = unsafe { from_utf8_unchecked(&self.bytes[self.layout.words.clone()]) };
+
+        self.range_at(&self.layout.word_ends, id)
+            .and_then(|r| words.get(r))
+            .unwrap_or_default()
+    }
+}
+
+#[derive(Clone, Copy)]
+struct FrozenRows<'a> {
+    index: FrozenIndex<'a>,
+    layout: &'a RowsLayout,
+}
+
+impl<'a> WordRows<'a> for FrozenRows<'a> {
+    fn docs(self, index: usize) -> MatchDocs<'a> {
+        let rows = &self.layout.rows;
+        let start = match index {
+            0 => Some(0),
+            index => self.index.u32_at(rows, index * 2 - 1),
+        };
+
+        let bytes = start
+            .zip(self.index.u32_at(rows, index * 2 + 1))
+            .and_then(|(start, end)| self.index.bytes[self.layout.bitmaps.clone()].get(start..end))
+            .unwrap_or_default();
+
+        // the run containers are not read in place, roaring does not write them.
+        match FrozenBitmap::new(bytes) {
+            Some(bitmap) => MatchDocs::Frozen(bitmap),
+            None => MatchDocs::Bitmap(Cow::Owned(
+                RoaringBitmap::deserialize_from(bytes).unwrap_or_default(),
+            )),
+        }
+    }
+
+    fn row_count(self) -> usize {
+        self.layout.rows.len() / 8
+    }
+
+    fn word(self, index: usize) -> &'a str {
+        match self.index.u32_at(&self.layout.rows, index * 2) {
+            Some(id) => self.index.word(id),
+            None => "",
+        }
+    }
+}
+
+/// A bitmap read in place from its portable serialization, as written by
+/// [RoaringBitmap::serialize_into], without run containers.
+///
+/// Only the containers needed are copied, when the docs are added to or
+/// intersected with a bitmap.
+#[derive(Clone, Copy)]
+pub(crate) struct FrozenBitmap<'a> {
+    bytes: &'a [u8],
+    len: usize,
+}
+
+impl<'a> FrozenBitmap<'a> {
+    const NO_RUN_COOKIE: u32 = 12346;
+
+    fn new(bytes: &'a [u8]) -> Option<Self> {
+        let section = 0..bytes.len();
+
+        if u32_at(bytes, &section, 0)? != Self::NO_RUN_COOKIE as usize {
+            return None;
+        }
+
+        let len = u32_at(bytes, &section, 1)?;
+
+        // the keys, cardinalities and offsets of the containers.
+        (len <= 1 << 16 && 8 + len * 8 <= bytes.len()).then_some(Self { bytes, len })
+    }
+
+    /// Gets the bitmap of a container, `None` when out of the bytes.
+    fn container(self, index: usize) -> Option<RoaringBitmap> {
+        let descriptions = 8..8 + self.len * 4;
+        let description = u32_at(self.bytes, &descriptions, index)?;
+        let high = (description as u32 & 0xFFFF) << 16;
+        let len = (description >> 16) + 1;
+
+        let offsets = descriptions.end..descriptions.end + self.len * 4;
+        let start = u32_at(self.bytes, &offsets, index)?;
+
+        match len <= 4096 {
+            true => {
+                let values = self.bytes.get(start..start + len * 2)?;
+                let values = values
+                    .chunks_exact(2)
+                    .map(|v| high | u32::from(u16::from_le_bytes([v[0], v[1]])));
+
+                RoaringBitmap::from_sorted_iter(values).ok()
+            }
+            false => {
+                let bits = self.bytes.get(start..start + 8192)?;
+                Some(RoaringBitmap::from_lsb0_bytes(high, bits))
+            }
+        }
+    }
+
+    /// Gets the range of the values of a container.
+    fn container_range(self, index: usize) -> Option<RangeInclusive<u32>> {
+        let key = u32_at(self.bytes, &(8..8 + self.len * 4), index)? as u32 & 0xFFFF;
+        Some(key << 16..=key << 16 | 0xFFFF)
+    }
+
+    pub(crate) fn intersect_into(self, docs: &mut RoaringBitmap) {
+        let mut result = RoaringBitmap::new();
+
+        for index in 0..self.len {
+            let Some(range) = self.container_range(index) else {
+                break;
+            };
+
+            // the containers without docs in common are not read.
+            if docs.range_cardinality(range.clone()) == 0 {
+                continue;
+            }
+
+            if let Some(mut container) = self.container(index) {
+                if !docs.contains_range(range) {
+                    container &= &*docs;
+                }
+
+                result |= container;
+            }
+        }
+
+        *docs = result;
+    }
+
+    pub(crate) fn union_into(self, docs: &mut RoaringBitmap) {
+        for container in (0..self.len).map_while(|index| self.container(index)) {
+            *docs |= container;
+        }
+    }
+}
+
+/// Gets the range of an item from a section of cumulative end offsets.
+fn range_at(bytes: &[u8], ends: &Range<usize>, index: usize) -> Option<Range<usize>> {
+    let start = match index {
+        0 => 0,
+        index => u32_at(bytes, ends, index - 1)?,
+    };
+
+    let end = u32_at(bytes, ends, index)?;
+
+    (start <= end).then_some(start..end)
+}
+
+fn u32_at(bytes: &[u8], section: &Range<usize>, index: usize) -> Option<usize> {
+    let start = section.start.checked_add(index.checked_mul(4)?)?;
+
+    if start + 4 > section.end {
+        return None;
+    }
+
+    let bytes = bytes.get(start..start + 4)?;
+    Some(u32::from_le_bytes(bytes.try_into().ok()?) as usize)
+}
+
+fn partition_point<P>(len: usize, mut pred: P) -> usize
+where
+    P: FnMut(usize) -> bool,
+{
+    let (mut low, mut high) = (0, len);
+
+    while low < high {
+        let mid = low + (high - low) / 2;
+
+        if pred(mid) {
+            low = mid + 1;
+        } else {
+            high = mid;
+        }
+    }
+
+    low
+}
+
+fn trigram_key(t: Trigram) -> u32 {
+    u32::from_le_bytes([t[0], t[1], t[2], 0])
+}
+
+/// Reads the length of a section of fixed size items and skips over it.
+fn section(data: &[u8], r: &mut &[u8], item_size: usize) -> io::Result<Range<usize>> {
+    let len = read_len(r)?
+        .checked_mul(item_size)
+        .filter(|len| *len <= r.len())
+        .ok_or_else(|| invalid_data("truncated section"))?;
+
+    let start = data.len() - r.len();
+
+    *r = &r[len..];
+    Ok(start..start + len)
+}
+
+/// Writes an index in the layout read by [FrozenLayout::read].
+pub(crate) fn write_index<W: Write>(w: &mut W, index: &Index) -> io::Result<()> {
+    let word_intern = index.word_intern();
+    let word_id = |word: &str| {
+        word_intern
+            .position(word)
+            .ok_or_else(|| invalid_data("word not found in vocabulary"))
+    };
+
+    write_ends(w, word_intern.iter().map(str::len))?;
+    write_len(w, word_intern.iter().map(str::len).sum())?;
+    word_intern
+        .iter()
+        .try_for_each(|word| w.write_all(word.as_bytes()))?;
+
+    let docs = index.docs();
+    let doc_attrs = || docs.iter().flat_map(|doc| doc.attrs.iter());
+
+    write_ends(w, docs.iter().map(|doc| doc.attrs.len()))?;
+    write_ends(w, doc_attrs().map(|attr| attr.words().len()))?;
+    write_len(w, doc_attrs().map(|attr| attr.words().len()).sum())?;
+
+    for word in doc_attrs().flat_map(|attr| attr.words()) {
+        write_len(w, word_id(unsafe { &**word })?)?;
+    }
+
+    write_ends(w, doc_attrs().map(|attr| attr.ranges().len()))?;
+    write_len(w, doc_attrs().map(|attr| attr.ranges().len()).sum())?;
+
+    for range in doc_attrs().flat_map(|attr| attr.ranges()) {
+        write_u32(w, range.start)?;
+        write_u32(w, range.end)?;
+    }
+
+    let mut word_trigrams = word_intern
+        .iter()
+        .enumerate()
+        .flat_map(|(id, word)| {
+            unique_trigrams(word)
+                .into_iter()
+                .map(move |t| (trigram_key(t), id))
+        })
+        .collect::<Vec<_>>();
+
+    word_trigrams.sort_unstable();
+
+    let groups = word_trigrams.chunk_by(|a, b| a.0 == b.0);
+
+    write_len(w, groups.clone().count())?;
+    groups.clone().try_for_each(|g| write_u32(w, g[0].0))?;
+    write_ends(w, groups.map(<[_]>::len))?;
+    write_len(w, word_trigrams.len())?;
+    word_trigrams
+        .iter()
+        .try_for_each(|(_, id)| write_len(w, *id))?;
+
+    write_len(w, index.groups().len())?;
+
+    for group in index.groups() {
+        write_len(w, group.per_culture().len())?;
+
+        for (word_index, stop_index) in group.per_culture().iter().zip(group.stop_rows()) {
+            write_rows(w, word_index, word_id)?;
+            write_rows(w, stop_index, word_id)?;
+        }
+
+        group.stop_words().write_to(w)?;
+    }
+
+    Ok(())
+}
+
+/// Writes the rows and the bitmaps of a word index in the layout read by
+/// [RowsLayout::read].
+fn write_rows<W, F>(w: &mut W, word_index: &WordIndex, word_id: F) -> io::Result<()>
+where
+    W: Write,
+    F: Fn(&str) -> io::Result<usize>,
+{
+    let mut end = 0;
+
+    write_len(w, word_index.row_count())?;
+
+    for row in 0..word_index.row_count() {
+        end += word_index.row_docs(row).serialized_size();
+
+        write_len(w, word_id(word_index.word(row))?)?;
+        write_len(w, end)?;
+    }
+
+    write_len(w, end)?;
+
+    for row in 0..word_index.row_count() {
+        write_bitmap(w, word_index.row_docs(row))?;
+    }
+
+    Ok(())
+}
+
+/// Writes the stored values in the layout read by [StoredLayout::read].
+pub(crate) fn write_stored<W: Write>(w: &mut W, stored: &StoredValues) -> io::Result<()> {
+    let values = || stored.docs().flat_map(|values| values.iter());
+    let len = |v: &Option<Box<str>>| v.as_deref().map_or(0, str::len);
+
+    write_ends(w, stored.docs().map(<[_]>::len))?;
+    write_ends(w, values().map(len))?;
+    write_len(w, values().map(len).sum())?;
+
+    values()
+        .flatten()
+        .try_for_each(|v| w.write_all(v.as_bytes()))
+}
+
+/// Writes the count of items followed by their cumulative end offsets.
+fn write_ends<W, I>(w: &mut W, lens: I) -> io::Result<()>
+where
+    W: Write,
+    I: Clone + Iterator<Item = usize>,
+{
+    let mut end = 0;
+
+    write_len(w, lens.clone().count())?;
+
+    for len in lens {
+        end += len;
+        write_len(w, end)?;
+    }
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{compare, AttrProps, Searcher};
+
+    fn searcher() -> Searcher {
+        let mut searcher = Searcher::new();
+        searcher.set_attribute("en".into(), AttrProps::default().culture(0));
+        searcher.set_attribute("fr".into(), AttrProps::default().culture(1));
+        searcher.set_attribute(
+            "*".into(),
+            AttrProps::default().direction(Direction::Backward),
+        );
+
+        searcher.insert_doc_attribute(DocId::from(0), "en", "country");
+        searcher.insert_doc_attribute(DocId::from(1), "en", "count topic");
+        searcher.insert_doc_attribute(DocId::from(1), "fr", "encours");
+        searcher.insert_doc_attribute(DocId::from(3), "*", "air balle");
+        searcher
+    }
+
+    #[test]
+    fn query_and_compare() {
+        let searcher = searcher();
+        let mut buf = Vec::new();
+
+        searcher.save_frozen_to(&mut buf).unwrap();
+
+        let frozen = FrozenSearcher::open(buf).unwrap();
+
+        for (culture, text) in [
+            (0, "count"),
+            (0, "count topic"),
+            (1, "encour"),
+            (0, "*alle"),
+            (0, "coun*"),
+            (0, "*opi*"),
+            (0, "*ntry"),
+            (0, "'country'"),
+            (1, "country"),
+        ] {
+            let query = SearchQuery::new(culture, text);
+            let live = searcher.query(&query);
+            let results = frozen.query(&query);
+
+            for id in 0..4 {
+                let id = DocId::from(id);
+                assert_eq!(live.contains_doc_id(id), results.contains_doc_id(id));
+            }
+
+            assert_eq!(
+                compare(DocId::from(0), &live, DocId::from(1), &live),
+                compare(DocId::from(0), &results, DocId::from(1), &results),
+            );
+        }
+
+        assert_eq!(
+            frozen
+                .get_doc_attr_words(DocId::from(1), "en")
+                .collect::<Vec<_>>(),
+            vec!["count", "topic"]
+        );
+
+        assert_eq!(
+            frozen
+                .get_doc_attr_words(DocId::from(3), "*")
+                .collect::<Vec<_>>(),
+            vec!["ria", "ellab"]
+        );
+
+        assert_eq!(frozen.get_doc_attr_words(DocId::from(2), "en").count(), 0);
+    }
+
+    #[test]
+    fn stored_values() {
+        let mut searcher = searcher();
+        searcher.set_attribute("title".into(), AttrProps::default().store(true));
+        searcher.insert_doc_attribute(DocId::from(2), "title", "The count, of topics.");
+
+        let mut buf = Vec::new();
+        searcher.save_frozen_to(&mut buf).unwrap();
+
+        let frozen = FrozenSearcher::open(buf).unwrap();
+        let results = frozen.query(&SearchQuery::new(0, "count"));
+
+        assert_eq!(
+            frozen.get_doc_attr_value(DocId::from(2), "title"),
+            Some("The count, of topics.")
+        );
+
+        assert_eq!(frozen.get_doc_attr_value(DocId::from(1), "title"), None);
+        assert_eq!(frozen.get_doc_attr_value(DocId::from(2), "en"), None);
+
+        assert_eq!(
+            results
+                .highlight(DocId::from(2), "title", "<", ">")
+                .as_deref(),
+            Some("The <count>, of topics.")
+        );
+
+        assert_eq!(
+            results
+                .snippet_highlighted(DocId::from(2), "title", 2, "<", ">")
+                .as_deref(),
+            Some("The <count>…")
+        );
+
+        // the words are highlighted by the ranges found by their tokenizer.
+        let mut searcher = self::searcher();
+        searcher.register_tokenizer(
+            "identifier",
+            std::sync::Arc::new(crate::IdentifierTokenizer),
+        );
+        searcher.set_attribute(
+            "code".into(),
+            AttrProps::default().tokenizer("identifier").store(true),
+        );
+        searcher.insert_doc_attribute(DocId::from(2), "code", "ABC-123 count");
+
+        let mut buf = Vec::new();
+        searcher.save_frozen_to(&mut buf).unwrap();
+
+        let frozen = FrozenSearcher::open(buf).unwrap();
+        let results = frozen.query(&SearchQuery::new(0, "count"));
+
+        assert_eq!(
+            results
+                .highlight(DocId::from(2), "code", "<", ">")
+                .as_deref(),
+            Some("ABC-123 <count>")
+        );
+    }
+
+    #[test]
+    fn bitmap_in_place() {
+        let bitmap = (0..10_000)
+            .chain(70_000..70_010)
+            .chain([1 << 20])
+            .collect::<RoaringBitmap>();
+
+        let mut bytes = Vec::new();
+        bitmap.serialize_into(&mut bytes).unwrap();
+
+        let frozen = FrozenBitmap::new(&bytes).unwrap();
+
+        let mut docs = RoaringBitmap::from_iter([5, 20_000]);
+        frozen.union_into(&mut docs);
+        assert_eq!(docs, &bitmap | RoaringBitmap::from_iter([20_000]));
+
+        let mut docs = RoaringBitmap::full();
+        frozen.intersect_into(&mut docs);
+        assert_eq!(docs, bitmap);
+
+        let mut docs = (5_000..70_005).collect::<RoaringBitmap>();
+        frozen.intersect_into(&mut docs);
+        assert_eq!(
+            docs,
+            (5_000..10_000)
+                .chain(70_000..70_005)
+                .collect::<RoaringBitmap>()
+        );
+
+        assert!(FrozenBitmap::new(&bytes[..12]).is_none());
+    }
+
+    #[test]
+    fn custom_analyzers() {
+        struct Whitespace;
+
+        impl crate::Tokenizer for Whitespace {
+            fn tokenize(&self, text: &str, sink: &mut dyn FnMut(&str, Range<usize>)) {
+                for word in text.split_whitespace() {
+                    let start = word.as_ptr() as usize - text.as_ptr() as usize;
+                    sink(word, start..start + word.len());
+                }
+            }
+        }
+
+        let mut searcher = searcher();
+        searcher.register_tokenizer("whitespace", Arc::new(Whitespace));
+        searcher.set_culture_tokenizer(1, Arc::new(Whitespace));
+        searcher.set_attribute("code".into(), AttrProps::default().tokenizer("whitespace"));
+        searcher.insert_doc_attribute(DocId::from(4), "code", "AB-12 x");
+        searcher.insert_doc_attribute(DocId::from(5), "code", "AB 12");
+        searcher.insert_doc_attribute(DocId::from(5), "fr", "en-cours");
+
+        // the words of the query are normalized again by attribute.
+        let lowercase = Arc::new(crate::StandardNormalizer::lowercase());
+        searcher.register_normalizer("lowercase", lowercase.clone());
+        searcher.set_attribute("name".into(), AttrProps::default().normalizer("lowercase"));
+        searcher.insert_doc_attribute(DocId::from(6), "name", "Café");
+        searcher.insert_doc_attribute(DocId::from(7), "name", "Cafe");
+
+        let mut buf = Vec::new();
+        searcher.save_frozen_to(&mut buf).unwrap();
+
+        let mut frozen = FrozenSearcher::open(buf).unwrap();
+        frozen.register_tokenizer("whitespace", Arc::new(Whitespace));
+        frozen.set_culture_tokenizer(1, Arc::new(Whitespace));
+        frozen.register_normalizer("lowercase", lowercase);
+
+        for (culture, text) in [
+            (0, "AB-12"),
+            (0, "\"AB-12\" x"),
+            (0, "ab 12"),
+            (1, "en-cours"),
+            (1, "encours"),
+            (0, "'café'"),
+            (0, "'cafe'"),
+        ] {
+            let live = searcher.parse_query(culture, text);
+            let query = frozen.parse_query(culture, text);
+
+            assert_eq!(
+                searcher.query(&live).doc_ids(),
+                frozen.query(&query).doc_ids(),
+                "{text}"
+            );
+        }
+
+        let query = SearchQuery::new(0, "AB-12");
+
+        assert_eq!(
+            frozen.query(&query).iter().collect::<Vec<_>>(),
+            vec![DocId::from(4)]
+        );
+        assert_eq!(
+            searcher.query(&query).doc_ids(),
+            frozen.query(&query).doc_ids()
+        );
+
+        let query = SearchQuery::new(0, "'café'");
+
+        assert_eq!(
+            frozen.query(&query).iter().collect::<Vec<_>>(),
+            vec![DocId::from(6)]
+        );
+    }
+
+    #[test]
+    fn open_truncated() {
+        let mut buf = Vec::new();
+
+        searcher().save_frozen_to(&mut buf).unwrap();
+        buf.truncate(buf.len() - 1);
+
+        assert!(FrozenSearcher::open(buf).is_err());
+    }
+}
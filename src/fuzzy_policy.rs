@@ -0,0 +1,124 @@
+use crate::binary::{read_bool, read_u8, write_bool, write_u8};
+use std::{
+    cmp::min,
+    io::{self, Read, Write},
+};
+
+/// How many typos a fuzzy query word tolerates, set on a
+/// [SearchQuery](crate::SearchQuery) and overridden by attribute with
+/// [AttrProps](crate::AttrProps).
+///
+/// By default, words of 3 to 5 chars tolerate 1 typo, 6 to 8 chars 2 typos and
+/// 9 chars and more 3 typos; shorter words are matched as prefixes.
+#[derive(Clone, Copy, Debug, Eq, Hash, PartialEq)]
+pub struct FuzzyPolicy {
+    pub(crate) first_char_exact: bool,
+    pub(crate) max_distance: u8,
+    pub(crate) min_lens: [u8; 3],
+    pub(crate) transpositions: bool,
+}
+
+impl FuzzyPolicy {
+    /// Requires the first char of a word, the last one for backward
+    /// attributes, to match without typo.
+    pub fn first_char_exact(mut self, first_char_exact: bool) -> Self {
+        self.first_char_exact = first_char_exact;
+        self
+    }
+
+    /// The max number of typos whatever the length of a word, at most 3. With 0,
+    /// the words are matched as prefixes.
+    pub fn max_distance(mut self, max_distance: u8) -> Self {
+        self.max_distance = min(max_distance, 3);
+        self
+    }
+
+    /// The min number of chars of a word to tolerate 1, 2 and 3 typos.
+    pub fn min_lens(mut self, one: u8, two: u8, three: u8) -> Self {
+        self.min_lens = [one, two, three];
+        self
+    }
+
+    /// Counts the swap of two adjacent chars as one typo instead of two.
+    pub fn transpositions(mut self, transpositions: bool) -> Self {
+        self.transpositions = transpositions;
+        self
+    }
+
+    /// The number of typos tolerated for a word.
+    pub(crate) fn distance(&self, word: &str) -> u8 {
+        let len = word.chars().count();
+        let distance = self
+            .min_lens
+            .iter()
+            .take_while(|&&min_len| len >= min_len as usize)
+            .count() as u8;
+
+        min(distance, self.max_distance)
+    }
+
+    pub(crate) fn read_from<R: Read>(r: &mut R) -> io::Result<Self> {
+        Ok(Self {
+            first_char_exact: read_bool(r)?,
+            max_distance: min(read_u8(r)?, 3),
+            min_lens: [read_u8(r)?, read_u8(r)?, read_u8(r)?],
+            transpositions: read_bool(r)?,
+        })
+    }
+
+    pub(crate) fn write_to<W: Write>(&self, w: &mut W) -> io::Result<()> {
+        write_bool(w, self.first_char_exact)?;
+        write_u8(w, self.max_distance)?;
+        self.min_lens.iter().try_for_each(|l| write_u8(w, *l))?;
+        write_bool(w, self.transpositions)
+    }
+}
+
+impl Default for FuzzyPolicy {
+    fn default() -> Self {
+        Self {
+            first_char_exact: false,
+            max_distance: 3,
+            min_lens: [3, 6, 9],
+            transpositions: true,
+        }
+    }
+}
+
+/// Reads a policy written by [write_fuzzy_policy].
+pub(crate) fn read_fuzzy_policy<R: Read>(r: &mut R) -> io::Result<Option<FuzzyPolicy>> {
+    match read_bool(r)? {
+        true => Ok(Some(FuzzyPolicy::read_from(r)?)),
+        false => Ok(None),
+    }
+}
+
+pub(crate) fn write_fuzzy_policy<W: Write>(
+    w: &mut W,
+    policy: Option<&FuzzyPolicy>,
+) -> io::Result<()> {
+    write_bool(w, policy.is_some())?;
+
+    match policy {
+        Some(policy) => policy.write_to(w),
+        None => Ok(()),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn distance() {
+        let policy = FuzzyPolicy::default();
+
+        assert_eq!(policy.distance("ab"), 0);
+        assert_eq!(policy.distance("abc"), 1);
+        assert_eq!(policy.distance("écolé"), 1);
+        assert_eq!(policy.distance("balance"), 2);
+        assert_eq!(policy.distance("balancement"), 3);
+        assert_eq!(policy.max_distance(1).distance("balancement"), 1);
+        assert_eq!(policy.min_lens(4, 5, 20).distance("balance"), 2);
+    }
+}
@@ -1,12 +1,23 @@
 use crate::{
-    AttrMap, Direction, DocId, MatchEntry, StrIntern, WordIndex, WordInternResolver, WordQuery,
-    WordQueryOp,
+    binary::{invalid_data, read_len, read_u32, write_len, write_u32},
+    searcher::{Attr, GroupKey},
+    stop_words::StopWords,
+    word_index::{WordPredicate, WordRows},
+    Analyzer, AttrMap, ChunkedVec, Direction, DocId, FuzzyPolicy, MatchEntry, StrIntern, WordIndex,
+    WordInternResolver, WordQuery, WordQueryOp,
 };
 use fxhash::FxHashSet;
-use std::{iter::Peekable, mem::take, str::Chars};
-use str_utils::char_map::lower_no_accent_char;
+#[cfg(feature = "rayon")]
+use rayon::prelude::*;
+use roaring::RoaringBitmap;
+use std::{
+    io::{self, Read, Write},
+    mem::take,
+    ops::Range,
+    sync::Arc,
+};
 
-#[derive(Default)]
+#[derive(Clone, Default)]
 pub(crate) struct Doc {
     pub(crate) attrs: Box<[DocAttr]>,
 }
@@ -41,39 +52,214 @@ impl Doc {
     }
 }
 
-#[derive(Default)]
+#[derive(Clone, Default)]
 pub(crate) struct DocAttr {
+    /// the byte ranges of the words in the value, giving their positions and
+    /// their highlights.
+    ranges: Box<[Range<u32>]>,
     words: Box<[*const str]>,
 }
 
+impl DocAttr {
+    pub(crate) fn ranges(&self) -> &[Range<u32>] {
+        &self.ranges
+    }
+
+    pub(crate) fn words(&self) -> &[*const str] {
+        &self.words
+    }
+}
+
+unsafe impl Send for Doc {}
+unsafe impl Sync for Doc {}
+
+/// A cloned index shares its docs, word rows and interned words with the
+/// original by chunks, a chunk being copied when it is modified.
+///
+/// The words of the attributes are indexed by group of attributes, see
+/// [GroupKey], a word matched in a group being in an attribute of the group.
+#[derive(Clone)]
 pub(crate) struct Index {
     direction: Direction,
-    docs: Vec<Doc>,
-    per_culture: Vec<WordIndex>,
+    docs: ChunkedVec<Arc<Doc>>,
+    groups: Vec<WordGroup>,
     word_intern: StrIntern,
 }
 
 impl Index {
-    pub(crate) const fn new(direction: Direction) -> Self {
+    pub(crate) fn new(direction: Direction) -> Self {
         Self {
             direction,
-            docs: Vec::new(),
-            per_culture: Vec::new(),
+            docs: ChunkedVec::new(),
+            groups: Vec::new(),
             word_intern: StrIntern::new(),
         }
     }
 
-    #[cfg(test)]
-    pub(crate) fn docs(&self) -> &[Doc] {
-        &self.docs
-    }
+    /// Inserts the words of many doc attributes, as inserting them one by one,
+    /// with at most one entry by doc attribute.
+    ///
+    /// The words of the attributes without words yet are sorted once and merged
+    /// in the vocabulary and the word indexes in a single pass, the others are
+    /// replaced one by one.
+    pub(crate) fn bulk_insert(
+        &mut self,
+        entries: Vec<BulkEntry>,
+        log: &mut IndexLog,
+        attrs: &AttrMap,
+    ) {
+        let (replaced, mut inserted): (Vec<_>, Vec<_>) = entries.into_iter().partition(|e| {
+            !self
+                .get_doc_attribute_words(e.doc_id, e.attr_index)
+                .is_empty()
+        });
 
-    pub(crate) fn ensure_culture(&mut self, attrs: &AttrMap, log: &mut IndexLog) {
-        if !attrs.values().any(|a| a.direction == self.direction) {
-            self.per_culture = Vec::new();
-            return;
+        let cultures = self.culture_count();
+
+        // entries whose words would not be inserted in any word index.
+        inserted.retain(|e| {
+            !e.words.is_empty()
+                && self
+                    .attr(attrs, e.attr_index)
+                    .is_some_and(|a| a.culture.is_none_or(|c| (c as usize) < cultures))
+        });
+
+        if self.direction == Direction::Backward {
+            for word in inserted.iter_mut().flat_map(|e| e.words.iter_mut()) {
+                *word = word.chars().rev().collect();
+            }
         }
 
+        let mut words = inserted
+            .iter()
+            .flat_map(|e| e.words.iter().map(|w| &**w))
+            .collect::<Vec<_>>();
+
+        words.sort_unstable();
+        words.dedup();
+
+        self.word_intern.extend_sorted(&words);
+
+        let mut postings =
+            vec![vec![Vec::<(&'static str, u32)>::new(); cultures]; self.groups.len()];
+        let mut stop_postings = postings.clone();
+
+        for e in inserted {
+            let attr = self.attr(attrs, e.attr_index).expect("attr");
+            let culture = attr.culture;
+            let words = e
+                .words
+                .iter()
+                .map(|w| self.word_intern.get_str(w).expect("interned word"))
+                .collect::<Vec<_>>();
+
+            let culture_postings = word_indexes(culture, &mut postings[attr.group])
+                .iter_mut()
+                .zip(word_indexes(culture, &mut stop_postings[attr.group]));
+
+            let group = &self.groups[attr.group];
+
+            for (i, (culture_postings, culture_stop_postings)) in culture_postings.enumerate() {
+                let culture = culture.map_or(i, usize::from);
+
+                for w in &words {
+                    match group.is_stop_word(culture, self.direction, w) {
+                        true => culture_stop_postings.push((*w, e.doc_id.0)),
+                        false => culture_postings.push((*w, e.doc_id.0)),
+                    }
+                }
+            }
+
+            self.docs.grow_with(e.doc_id.index() + 1, Default::default);
+
+            let doc = Arc::make_mut(self.docs.get_mut(e.doc_id.index()).expect("doc"));
+
+            doc.ensure_attrs_size(e.attr_index + 1);
+            doc.attrs[e.attr_index] = DocAttr {
+                ranges: e.ranges.into_boxed_slice(),
+                words: words.into_iter().map(|w| w as *const str).collect(),
+            };
+        }
+
+        let merge = |(word_index, postings): (&mut WordIndex, _)| {
+            word_index.extend_sorted(word_rows(postings));
+        };
+
+        let word_indexes = self
+            .groups
+            .iter_mut()
+            .flat_map(|g| g.per_culture.iter_mut().chain(&mut g.stop_rows));
+        let postings = postings
+            .into_iter()
+            .zip(stop_postings)
+            .flat_map(|(p, stop_p)| p.into_iter().chain(stop_p));
+
+        #[cfg(feature = "rayon")]
+        word_indexes
+            .collect::<Vec<_>>()
+            .into_par_iter()
+            .zip(postings.collect::<Vec<_>>())
+            .for_each(merge);
+
+        #[cfg(not(feature = "rayon"))]
+        word_indexes.zip(postings).for_each(merge);
+
+        for e in replaced {
+            let Some(attr) = self.attr(attrs, e.attr_index) else {
+                continue;
+            };
+
+            let mut words = Vec::<*const str>::with_capacity(e.words.len());
+            let mut ranges = Vec::with_capacity(e.ranges.len());
+
+            for (i, w) in e.words.iter().enumerate() {
+                let word = insert_doc_word(
+                    &mut self.groups[attr.group],
+                    attr.culture,
+                    directional_word(w, self.direction, &mut log.str),
+                    w,
+                    e.doc_id,
+                    &mut self.word_intern,
+                );
+
+                if let Some(word) = word {
+                    words.push(word);
+                    ranges.extend(e.ranges.get(i).cloned());
+                }
+            }
+
+            self.set_doc_attr_words(e.doc_id, e.attr_index, words, ranges, log);
+        }
+    }
+
+    /// Gets an attribute of the direction of the index by its index.
+    fn attr<'a>(&self, attrs: &'a AttrMap, attr_index: usize) -> Option<&'a Attr> {
+        attrs
+            .values()
+            .find(|a| a.direction == self.direction && a.index == attr_index)
+    }
+
+    /// The number of cultures of the word indexes of each group.
+    fn culture_count(&self) -> usize {
+        self.groups.first().map_or(0, |g| g.per_culture.len())
+    }
+
+    pub(crate) fn docs(&self) -> &ChunkedVec<Arc<Doc>> {
+        &self.docs
+    }
+
+    /// Sets the word indexes of a group to one per culture, the new cultures
+    /// filled with the words of the attributes of the group without culture.
+    ///
+    /// The new cultures have no stop words until they are normalized for the
+    /// group, see [Index::set_stop_words].
+    fn ensure_culture(&mut self, attrs: &AttrMap, group: usize) {
+        let WordGroup {
+            per_culture,
+            stop_rows,
+            stop_words,
+            ..
+        } = &mut self.groups[group];
         let count = attrs
             .values()
             .filter_map(|a| a.culture)
@@ -81,22 +267,22 @@ impl Index {
             .unwrap_or_default() as usize
             + 1;
 
-        if self.per_culture.len() == count {
+        if per_culture.len() == count {
             return;
         }
 
-        if self.per_culture.len() > count {
-            while self.per_culture.len() > count {
-                self.per_culture.pop();
-            }
+        if per_culture.len() > count {
+            per_culture.truncate(count);
+            stop_rows.truncate(count);
+            stop_words.truncate(count);
             return;
         }
 
-        let is_empty = self.per_culture.is_empty();
-        let range = self.per_culture.len()..count;
+        let is_empty = per_culture.is_empty();
+        let range = per_culture.len()..count;
 
-        self.per_culture
-            .extend(range.clone().map(|_| WordIndex::new()));
+        per_culture.extend(range.clone().map(|_| WordIndex::new()));
+        stop_rows.extend(range.clone().map(|_| WordIndex::new()));
 
         if is_empty {
             return;
@@ -104,7 +290,7 @@ impl Index {
 
         let attr_indexes = attrs
             .values()
-            .filter(|a| a.culture.is_none() && a.direction == self.direction)
+            .filter(|a| a.culture.is_none() && a.direction == self.direction && a.group == group)
             .map(|a| a.index)
             .collect::<Vec<_>>();
 
@@ -112,35 +298,73 @@ impl Index {
             return;
         }
 
-        let word_indexes = self.per_culture.get_mut(range).expect("word_indexes");
+        // the words of the attributes without culture, in all the new cultures.
+        #[cfg(feature = "rayon")]
+        let postings = self
+            .docs
+            .par_enumerate()
+            .flat_map_iter(|(index, doc)| doc_postings(doc, index, &attr_indexes))
+            .collect::<Vec<_>>();
 
-        for (index, doc) in self.docs.iter().enumerate() {
-            log.words.clear();
+        #[cfg(not(feature = "rayon"))]
+        let postings = self
+            .docs
+            .iter()
+            .enumerate()
+            .flat_map(|(index, doc)| doc_postings(doc, index, &attr_indexes))
+            .collect::<Vec<_>>();
 
-            for attr_index in &attr_indexes {
-                if let Some(attr) = doc.attrs.get(*attr_index) {
-                    log.words.extend(attr.words.iter().copied());
-                }
-            }
+        let rows = word_rows(postings);
+        let fill = |word_index: &mut WordIndex| word_index.extend_sorted(rows.clone());
+        let per_culture = &mut self.groups[group].per_culture[range];
 
-            let doc_id = DocId::from(index as u32);
+        #[cfg(feature = "rayon")]
+        per_culture.par_iter_mut().for_each(fill);
 
-            for word in &log.words {
-                let word = unsafe { &**word };
+        #[cfg(not(feature = "rayon"))]
+        per_culture.iter_mut().for_each(fill);
+    }
 
-                for wi in &mut *word_indexes {
-                    wi.insert_word_doc(word, WordInternResolver::StaticWord(word), doc_id);
-                }
-            }
+    /// Sets the groups of word indexes to the groups of the attributes of the
+    /// direction, a group keeping the word indexes of the former group with
+    /// the same key.
+    pub(crate) fn ensure_groups(&mut self, attrs: &AttrMap) {
+        let mut former = take(&mut self.groups);
+
+        for (group, key) in group_keys(attrs, self.direction).into_iter().enumerate() {
+            let (per_culture, stop_rows, stop_words) =
+                match former.iter().position(|g| g.key == key) {
+                    Some(i) => {
+                        let g = former.swap_remove(i);
+                        (g.per_culture, g.stop_rows, g.stop_words)
+                    }
+                    None => Default::default(),
+                };
+
+            self.groups.push(WordGroup {
+                key,
+                per_culture,
+                stop_rows,
+                stop_words,
+            });
+            self.ensure_culture(attrs, group);
+        }
+    }
+
+    pub(crate) fn get_doc_attribute_ranges(&self, id: DocId, attr_index: usize) -> &[Range<u32>] {
+        match self.get_doc_attribute(id, attr_index) {
+            Some(attr) => &attr.ranges,
+            None => Default::default(),
         }
     }
 
+    fn get_doc_attribute(&self, id: DocId, attr_index: usize) -> Option<&DocAttr> {
+        self.docs.get(id.index())?.attrs.get(attr_index)
+    }
+
     pub(crate) fn get_doc_attribute_words(&self, id: DocId, attr_index: usize) -> &[*const str] {
-        match self.docs.get(id.index()) {
-            Some(doc) => match doc.attrs.get(attr_index) {
-                Some(attr) => &attr.words,
-                None => Default::default(),
-            },
+        match self.get_doc_attribute(id, attr_index) {
+            Some(attr) => &attr.words,
             None => Default::default(),
         }
     }
@@ -150,24 +374,46 @@ impl Index {
         doc_id: DocId,
         attribute_index: usize,
         value: &str,
+        analyzer: Analyzer,
         log: &mut IndexLog,
         attrs: &AttrMap,
     ) {
-        let Some((_, a)) = attrs.get_index(attribute_index) else {
+        let Some(a) = self.attr(attrs, attribute_index) else {
             return;
         };
 
-        let word_indexes = word_indexes(a.culture, &mut self.per_culture);
+        let mut new_word_list = Vec::<*const str>::new();
+        let mut ranges = Vec::new();
+
+        analyzer.words(value, &mut |word, range| {
+            let word = insert_doc_word(
+                &mut self.groups[a.group],
+                a.culture,
+                directional_word(word, self.direction, &mut log.str),
+                word,
+                doc_id,
+                &mut self.word_intern,
+            );
+
+            if let Some(word) = word {
+                new_word_list.push(word);
+                ranges.push(range.start as u32..range.end as u32);
+            }
+        });
 
-        let new_word_list = insert_doc_word_list(
-            word_indexes,
-            value,
-            doc_id,
-            log,
-            &mut self.word_intern,
-            self.direction,
-        );
+        self.set_doc_attr_words(doc_id, attribute_index, new_word_list, ranges, log);
+    }
 
+    /// Replaces the words of a doc attribute, the words already inserted in the
+    /// word indexes, removing the doc from the words it no longer contains.
+    fn set_doc_attr_words(
+        &mut self,
+        doc_id: DocId,
+        attribute_index: usize,
+        new_word_list: Vec<*const str>,
+        ranges: Vec<Range<u32>>,
+        log: &mut IndexLog,
+    ) {
         let doc = match self.docs.get_mut(doc_id.index()) {
             Some(doc) => doc,
             None => {
@@ -176,11 +422,13 @@ impl Index {
                     return;
                 }
 
-                ensure_size(&mut self.docs, doc_id.0 as usize, Default::default);
+                self.docs.grow_with(doc_id.index() + 1, Default::default);
                 self.docs.get_mut(doc_id.index()).expect("doc")
             }
         };
 
+        let doc = Arc::make_mut(doc);
+
         let doc_attr = match doc.attrs.get_mut(attribute_index) {
             Some(attr) => attr,
             None => {
@@ -201,35 +449,164 @@ impl Index {
             log.words.remove(w);
         });
 
+        doc_attr.ranges = ranges.into_boxed_slice();
         doc_attr.words = new_word_list.into_boxed_slice();
         log.words.retain(|w| !doc.contains_word(*w));
 
         self.remove_words_doc(&log.words, doc_id);
     }
 
+    pub(crate) fn groups(&self) -> &[WordGroup] {
+        &self.groups
+    }
+
+    /// The word indexes of the first group, the only one when the attributes
+    /// have the same key.
     #[cfg(test)]
     pub(crate) fn per_culture(&self) -> &[WordIndex] {
-        &self.per_culture
+        self.groups.first().map_or(&[], |g| &g.per_culture)
     }
 
-    pub(crate) fn query<'a>(&'a self, q: &WordQuery, culture: u8, out: &mut Vec<MatchEntry<'a>>) {
-        let culture = culture as usize;
+    /// Queries a word in the word index of a culture of a group.
+    pub(crate) fn query<'a>(
+        &'a self,
+        q: &WordQuery,
+        group: usize,
+        culture: u8,
+        policy: &FuzzyPolicy,
+        out: &mut Vec<MatchEntry<'a>>,
+    ) {
+        let Some(WordGroup { per_culture, .. }) = self.groups.get(group) else {
+            return;
+        };
 
-        if let Some(word_index) = self
-            .per_culture
-            .get(culture)
-            .or_else(|| self.per_culture.first())
+        if let Some(word_index) = per_culture
+            .get(culture as usize)
+            .or_else(|| per_culture.first())
         {
-            match q.op {
-                WordQueryOp::Contains => contains(self.direction, word_index, q, out),
-                WordQueryOp::EndsWith => ends_with(self.direction, word_index, q, out),
-                WordQueryOp::Eq => word_index.eq(q.directional_word(self.direction), out),
-                WordQueryOp::Fuzzy => fuzzy(self.direction, word_index, q, out),
-                WordQueryOp::StartsWith => starts_with(self.direction, word_index, q, out),
+            // only the candidate words of the trigram index are verified.
+            let candidates = trigram_match(self.direction, q).and_then(|(s, is_match)| {
+                Some((self.word_intern.containing_candidates(s)?, is_match))
+            });
+
+            match candidates {
+                Some((candidates, is_match)) => word_index.query_candidates(
+                    candidates.copied(),
+                    q.directional_word(self.direction),
+                    is_match,
+                    out,
+                ),
+                None => query_word_rows(self.direction, word_index, q, policy, out),
             }
         }
     }
 
+    /// Finds the docs of a stop word of a culture in a group, not in its word
+    /// index.
+    pub(crate) fn query_stop_word<'a>(
+        &'a self,
+        q: &WordQuery,
+        group: usize,
+        culture: u8,
+        out: &mut Vec<MatchEntry<'a>>,
+    ) {
+        let Some(WordGroup { stop_rows, .. }) = self.groups.get(group) else {
+            return;
+        };
+
+        if let Some(stop_index) = stop_rows
+            .get(culture as usize)
+            .or_else(|| stop_rows.first())
+        {
+            WordRows::eq(stop_index, q.directional_word(self.direction), out);
+        }
+    }
+
+    /// Checks if a word of a query is a stop word of a culture in a group, an
+    /// unknown culture being the first one as in [Index::query].
+    pub(crate) fn is_stop_word(&self, group: usize, culture: u8, word: &str) -> bool {
+        self.groups.get(group).is_some_and(|g| {
+            let culture = match (culture as usize) < g.per_culture.len() {
+                true => culture as usize,
+                false => 0,
+            };
+
+            g.stop_words.contains(culture, word)
+        })
+    }
+
+    /// Reads an index written by [Index::write_to], the interned words of the docs
+    /// and word indexes are resolved against the vocabulary read first.
+    pub(crate) fn read_from<R: Read>(
+        direction: Direction,
+        attrs: &AttrMap,
+        r: &mut R,
+    ) -> io::Result<Self> {
+        let word_intern = StrIntern::read_from(r)?;
+        let mut docs = ChunkedVec::new();
+
+        for _ in 0..read_len(r)? {
+            let mut attrs = Vec::new();
+
+            for _ in 0..read_len(r)? {
+                let mut words = Vec::<*const str>::new();
+
+                for _ in 0..read_len(r)? {
+                    let word = word_intern
+                        .get(read_u32(r)? as usize)
+                        .ok_or_else(|| invalid_data("word not found in vocabulary"))?;
+
+                    words.push(word);
+                }
+
+                let ranges = (0..read_len(r)?)
+                    .map(|_| Ok(read_u32(r)?..read_u32(r)?))
+                    .collect::<io::Result<_>>()?;
+
+                attrs.push(DocAttr {
+                    ranges,
+                    words: words.into_boxed_slice(),
+                });
+            }
+
+            docs.push(Arc::new(Doc {
+                attrs: attrs.into_boxed_slice(),
+            }));
+        }
+
+        let keys = group_keys(attrs, direction);
+
+        if read_len(r)? != keys.len() {
+            return Err(invalid_data("word groups not matching the attributes"));
+        }
+
+        let mut groups = Vec::new();
+
+        for key in keys {
+            let mut per_culture = Vec::new();
+            let mut stop_rows = Vec::new();
+
+            for _ in 0..read_len(r)? {
+                per_culture.push(WordIndex::read_from(r, &word_intern)?);
+                stop_rows.push(WordIndex::read_from(r, &word_intern)?);
+            }
+
+            groups.push(WordGroup {
+                key,
+                per_culture,
+                stop_rows,
+                stop_words: StopWords::read_from(r)?,
+            });
+        }
+
+        Ok(Self {
+            direction,
+            docs,
+            groups,
+            word_intern,
+        })
+    }
+
     pub(crate) fn remove_doc(&mut self, doc_id: DocId, log: &mut IndexLog) {
         let Some(doc) = self.docs.get_mut(doc_id.0 as usize) else {
             return;
@@ -251,7 +628,7 @@ impl Index {
         let word = unsafe { &*word };
         let mut word_to_delete = true;
 
-        for word_index in &mut self.per_culture {
+        for word_index in self.groups.iter_mut().flat_map(WordGroup::word_indexes_mut) {
             word_to_delete = word_index.remove_word_doc(word, doc_id) && word_to_delete;
         }
 
@@ -270,14 +647,29 @@ impl Index {
         &mut self,
         attribute_index: usize,
         culture: Option<u8>,
+        group: usize,
         log: &mut IndexLog,
     ) {
         log.words.clear();
 
-        let fast_delete = culture.is_none();
-        let word_indexes = word_indexes(culture, &mut self.per_culture);
+        // without culture, a word of no doc is in no other word index.
+        let fast_delete = culture.is_none() && self.groups.len() == 1;
+        let (word_indexes, stop_indexes) = match self.groups.get_mut(group) {
+            Some(g) => (
+                word_indexes(culture, &mut g.per_culture),
+                word_indexes(culture, &mut g.stop_rows),
+            ),
+            None => (&mut [][..], &mut [][..]),
+        };
+
+        for doc_index in 0..self.docs.len() {
+            // only the chunks of the docs having the attribute are copied.
+            if self.docs[doc_index].attrs.len() <= attribute_index {
+                continue;
+            }
+
+            let d = Arc::make_mut(self.docs.get_mut(doc_index).expect("doc"));
 
-        for (doc_index, d) in self.docs.iter_mut().enumerate() {
             let Some(doc_attr) = d.swap_remove_attr(attribute_index) else {
                 continue;
             };
@@ -297,6 +689,7 @@ impl Index {
                 let s = unsafe { &*word };
                 let check_to_clean = word_indexes
                     .iter_mut()
+                    .chain(stop_indexes.iter_mut())
                     .fold(true, |d, wi| wi.remove_word_doc(s, doc_id) && d);
 
                 if check_to_clean {
@@ -313,8 +706,9 @@ impl Index {
             let word = unsafe { &**w };
 
             if self
-                .per_culture
-                .iter_mut()
+                .groups
+                .iter()
+                .flat_map(WordGroup::word_indexes)
                 .all(|word_index| !word_index.contains_word(word))
             {
                 self.word_intern.remove(word);
@@ -322,89 +716,198 @@ impl Index {
         }
     }
 
-    #[cfg(test)]
+    /// Replaces the stop words of a culture in a group, normalized as its
+    /// words, the rows of the new stop words moving from the word index of the
+    /// culture to its stop rows, and the rows of the former ones back.
+    pub(crate) fn set_stop_words(&mut self, group: usize, culture: u8, words: FxHashSet<Box<str>>) {
+        let direction = self.direction;
+        let culture_index = culture as usize;
+        let mut temp = String::new();
+
+        let Some(group) = self
+            .groups
+            .get_mut(group)
+            .filter(|g| culture_index < g.per_culture.len())
+        else {
+            return;
+        };
+
+        let empty = FxHashSet::default();
+        let old = group.stop_words.culture(culture_index).unwrap_or(&empty);
+
+        if *old == words {
+            return;
+        }
+
+        let word_index = &mut group.per_culture[culture_index];
+        let stop_index = &mut group.stop_rows[culture_index];
+
+        move_rows(
+            words.difference(old),
+            direction,
+            word_index,
+            stop_index,
+            &mut temp,
+        );
+        move_rows(
+            old.difference(&words),
+            direction,
+            stop_index,
+            word_index,
+            &mut temp,
+        );
+
+        group.stop_words.set(culture, words);
+    }
+
     pub(crate) fn word_intern(&self) -> &StrIntern {
         &self.word_intern
     }
+
+    /// Writes the vocabulary and its trigrams followed by the docs, with the
+    /// byte ranges of their words, and the word indexes and the stop words of
+    /// each group, words are written as their position in the vocabulary.
+    pub(crate) fn write_to<W: Write>(&self, w: &mut W) -> io::Result<()> {
+        self.word_intern.write_to(w)?;
+        write_len(w, self.docs.len())?;
+
+        for doc in &self.docs {
+            write_len(w, doc.attrs.len())?;
+
+            for attr in &*doc.attrs {
+                write_len(w, attr.words.len())?;
+
+                for word in &*attr.words {
+                    let id = self
+                        .word_intern
+                        .position(unsafe { &**word })
+                        .ok_or_else(|| invalid_data("word not found in vocabulary"))?;
+
+                    write_u32(w, id as u32)?;
+                }
+
+                write_len(w, attr.ranges.len())?;
+
+                for range in &*attr.ranges {
+                    write_u32(w, range.start)?;
+                    write_u32(w, range.end)?;
+                }
+            }
+        }
+
+        write_len(w, self.groups.len())?;
+
+        for group in &self.groups {
+            write_len(w, group.per_culture.len())?;
+
+            for (word_index, stop_index) in group.per_culture.iter().zip(&group.stop_rows) {
+                word_index.write_to(w, &self.word_intern)?;
+                stop_index.write_to(w, &self.word_intern)?;
+            }
+
+            group.stop_words.write_to(w)?;
+        }
+
+        Ok(())
+    }
 }
 
 unsafe impl Send for Index {}
 unsafe impl Sync for Index {}
 
-#[derive(Default)]
-pub(crate) struct IndexLog {
-    str: String,
-    word: String,
-    words: FxHashSet<*const str>,
+/// The words of a doc attribute to insert with [Index::bulk_insert], with
+/// their byte ranges.
+pub(crate) struct BulkEntry {
+    pub(crate) attr_index: usize,
+    pub(crate) doc_id: DocId,
+    pub(crate) ranges: Vec<Range<u32>>,
+    pub(crate) words: Vec<String>,
 }
 
-unsafe impl Send for IndexLog {}
-unsafe impl Sync for IndexLog {}
+/// The word indexes of a group of attributes, by culture.
+#[derive(Clone)]
+pub(crate) struct WordGroup {
+    key: GroupKey,
+    per_culture: Vec<WordIndex>,
 
-fn word_indexes(culture: Option<u8>, per_culture: &mut [WordIndex]) -> &mut [WordIndex] {
-    match culture {
-        Some(culture) => {
-            let index = culture as usize;
-            per_culture.get_mut(index..(index + 1)).unwrap_or_default()
+    /// the rows of the stop words of each culture, kept out of its word index.
+    stop_rows: Vec<WordIndex>,
+
+    /// the stop words of each culture normalized as the words of the group.
+    stop_words: StopWords,
+}
+
+impl WordGroup {
+    /// Checks if a directional word is a stop word of a culture.
+    fn is_stop_word(&self, culture: usize, direction: Direction, word: &str) -> bool {
+        if self.stop_words.is_empty() {
+            return false;
         }
-        None => &mut per_culture[..],
+
+        let mut temp = String::new();
+        self.stop_words
+            .contains(culture, directional_word(word, direction, &mut temp))
     }
-}
 
-fn ensure_size<T, F>(vec: &mut Vec<T>, index: usize, mut new: F)
-where
-    F: FnMut() -> T,
-{
-    let r = vec.len()..(index + 1);
-    vec.extend(r.map(|_| new()));
-}
+    pub(crate) fn per_culture(&self) -> &[WordIndex] {
+        &self.per_culture
+    }
 
-fn find_next_word(chars: &mut Peekable<Chars>, word: &mut String) {
-    #[derive(Clone, Copy)]
-    enum CharKind {
-        Whitespace,
-        Alpha,
-        Number,
+    pub(crate) fn stop_rows(&self) -> &[WordIndex] {
+        &self.stop_rows
     }
 
-    let mut kind = CharKind::Whitespace;
+    pub(crate) fn stop_words(&self) -> &StopWords {
+        &self.stop_words
+    }
 
-    word.clear();
+    /// Iterates the word indexes and the stop rows of all the cultures.
+    fn word_indexes(&self) -> impl Iterator<Item = &WordIndex> {
+        self.per_culture.iter().chain(&self.stop_rows)
+    }
 
-    while let Some(&c) = chars.peek() {
-        if c.is_alphabetic() {
-            if !matches!(kind, CharKind::Alpha | CharKind::Whitespace) {
-                break;
-            }
+    fn word_indexes_mut(&mut self) -> impl Iterator<Item = &mut WordIndex> {
+        self.per_culture.iter_mut().chain(&mut self.stop_rows)
+    }
+}
 
-            word.extend(lower_no_accent_char(c));
-            kind = CharKind::Alpha;
-        } else if c.is_numeric() {
-            if !matches!(kind, CharKind::Number | CharKind::Whitespace) {
-                break;
-            }
+#[derive(Default)]
+pub(crate) struct IndexLog {
+    str: String,
+    words: FxHashSet<*const str>,
+}
 
-            word.push(c);
-            kind = CharKind::Number;
-        } else if c == '#' || c == '°' {
-            if matches!(kind, CharKind::Whitespace) {
-                chars.next();
-                word.push(c);
-            }
+unsafe impl Send for IndexLog {}
+unsafe impl Sync for IndexLog {}
 
-            break;
-        } else if !word.is_empty() {
-            chars.next();
-            break;
+/// Gets the keys of the groups of the attributes of a direction, by group, the
+/// groups being numbered in the order of their first attribute.
+fn group_keys(attrs: &AttrMap, direction: Direction) -> Vec<GroupKey> {
+    let mut keys = Vec::new();
+
+    for a in attrs.values().filter(|a| a.direction == direction) {
+        if a.group == keys.len() {
+            keys.push(a.group_key());
         }
+    }
 
-        chars.next();
+    keys
+}
+
+/// Gets the items of the cultures of an attribute, all of them for no culture.
+fn word_indexes<T>(culture: Option<u8>, per_culture: &mut [T]) -> &mut [T] {
+    match culture {
+        Some(culture) => {
+            let index = culture as usize;
+            per_culture.get_mut(index..(index + 1)).unwrap_or_default()
+        }
+        None => &mut per_culture[..],
     }
 }
 
-fn contains<'a>(
+fn contains<'a, R: WordRows<'a>>(
     direction: Direction,
-    word_index: &'a WordIndex,
+    word_index: R,
     q: &WordQuery,
     out: &mut Vec<MatchEntry<'a>>,
 ) {
@@ -427,9 +930,9 @@ fn directional_word<'a>(word: &'a str, direction: Direction, temp_str: &'a mut S
     }
 }
 
-fn ends_with<'a>(
+fn ends_with<'a, R: WordRows<'a>>(
     direction: Direction,
-    word_index: &'a WordIndex,
+    word_index: R,
     q: &WordQuery,
     out: &mut Vec<MatchEntry<'a>>,
 ) {
@@ -439,64 +942,151 @@ fn ends_with<'a>(
     }
 }
 
-fn fuzzy<'a>(
+fn fuzzy<'a, R: WordRows<'a>>(
     direction: Direction,
-    word_index: &'a WordIndex,
+    word_index: R,
     q: &WordQuery,
+    policy: &FuzzyPolicy,
     out: &mut Vec<MatchEntry<'a>>,
 ) {
-    match direction {
-        Direction::Backward => match q.backward_dfa() {
-            Some(dfa) => word_index.fuzzy(dfa, q.backward_word().len(), out),
-            None => word_index.ends_with(q.backward_word(), out),
-        },
-        Direction::Forward => match q.dfa() {
-            Some(dfa) => word_index.fuzzy(dfa, q.word.len(), out),
-            None => word_index.starts_with(&q.word, out),
+    let word = q.directional_word(direction);
+
+    match q.dfa(direction, policy) {
+        Some(dfa) => {
+            let prefix = match (policy.first_char_exact, word.chars().next()) {
+                (true, Some(c)) => &word[..c.len_utf8()],
+                _ => "",
+            };
+
+            word_index.fuzzy(&dfa, word.len(), prefix, out)
+        }
+        None => match direction {
+            Direction::Backward => word_index.ends_with(word, out),
+            Direction::Forward => word_index.starts_with(word, out),
         },
     }
 }
 
-fn insert_doc_word_list(
-    word_indexes: &mut [WordIndex],
-    attr_value: &str,
+/// Inserts a directional word of a doc in the word indexes of the cultures of
+/// an attribute, or in their stop rows where the word, as in the value, is a
+/// stop word, returns the interned word.
+fn insert_doc_word(
+    group: &mut WordGroup,
+    culture: Option<u8>,
+    word: &str,
+    value_word: &str,
     doc_id: DocId,
-    log: &mut IndexLog,
     word_intern: &mut StrIntern,
+) -> Option<*const str> {
+    let first = culture.map_or(0, usize::from);
+    let stop_words = &group.stop_words;
+    let word_indexes = word_indexes(culture, &mut group.per_culture)
+        .iter_mut()
+        .zip(word_indexes(culture, &mut group.stop_rows));
+    let mut interned = None::<&'static str>;
+
+    for (i, (word_index, stop_index)) in word_indexes.enumerate() {
+        let resolver = match interned {
+            Some(word) => WordInternResolver::StaticWord(word),
+            None => WordInternResolver::StrInter(word_intern),
+        };
+
+        let word_index = match stop_words.contains(first + i, value_word) {
+            true => stop_index,
+            false => word_index,
+        };
+
+        interned = Some(word_index.insert_word_doc(word, resolver, doc_id));
+    }
+
+    interned.map(|word| word as *const str)
+}
+
+/// Moves the rows of directional words from a word index to another.
+fn move_rows<'a, I>(
+    words: I,
     direction: Direction,
-) -> Vec<*const str> {
-    let mut chars = attr_value.chars().peekable();
-    let mut word_list = Vec::<*const str>::new();
+    from: &mut WordIndex,
+    to: &mut WordIndex,
+    temp: &mut String,
+) where
+    I: Iterator<Item = &'a Box<str>>,
+{
+    let mut rows = words
+        .filter_map(|w| from.remove_word(directional_word(w, direction, temp)))
+        .collect::<Vec<_>>();
 
-    loop {
-        find_next_word(&mut chars, &mut log.word);
+    rows.sort_unstable_by_key(|(word, _)| *word);
+    to.extend_sorted(rows);
+}
 
-        if log.word.is_empty() {
-            break;
-        }
+fn doc_postings<'a>(
+    doc: &'a Doc,
+    index: usize,
+    attr_indexes: &'a [usize],
+) -> impl Iterator<Item = (&'static str, u32)> + 'a {
+    attr_indexes
+        .iter()
+        .filter_map(|i| doc.attrs.get(*i))
+        .flat_map(|a| a.words.iter())
+        .map(move |w| (unsafe { &**w }, index as u32))
+}
 
-        let mut word_index_iter = word_indexes.iter_mut();
+/// Sorts the (word, doc) postings and groups the docs of each word, as rows
+/// to be merged in a word index.
+fn word_rows(mut postings: Vec<(&'static str, u32)>) -> Vec<(&'static str, RoaringBitmap)> {
+    #[cfg(feature = "rayon")]
+    postings.par_sort_unstable();
 
-        if let Some(word_index) = word_index_iter.next() {
-            let word = directional_word(&log.word, direction, &mut log.str);
+    #[cfg(not(feature = "rayon"))]
+    postings.sort_unstable();
 
-            let word =
-                word_index.insert_word_doc(word, WordInternResolver::StrInter(word_intern), doc_id);
+    postings.dedup();
 
-            for word_index in word_index_iter {
-                word_index.insert_word_doc(word, WordInternResolver::StaticWord(word), doc_id);
-            }
+    let mut rows = Vec::<(&'static str, RoaringBitmap)>::new();
 
-            word_list.push(word);
+    for (word, doc_id) in postings {
+        match rows.last_mut() {
+            Some((w, docs)) if *w == word => {
+                docs.push(doc_id);
+            }
+            _ => rows.push((word, RoaringBitmap::from_iter([doc_id]))),
         }
     }
 
-    word_list
+    rows
+}
+
+/// Queries the word rows of a culture, the query word is reversed for the backward index.
+pub(crate) fn query_word_rows<'a, R: WordRows<'a>>(
+    direction: Direction,
+    word_index: R,
+    q: &WordQuery,
+    policy: &FuzzyPolicy,
+    out: &mut Vec<MatchEntry<'a>>,
+) {
+    match q.op {
+        WordQueryOp::Contains => contains(direction, word_index, q, out),
+        WordQueryOp::EndsWith => ends_with(direction, word_index, q, out),
+        WordQueryOp::Eq => word_index.eq(q.directional_word(direction), out),
+        WordQueryOp::Fuzzy => fuzzy(direction, word_index, q, policy, out),
+        WordQueryOp::StartsWith => starts_with(direction, word_index, q, out),
+    }
+}
+
+/// Gets the string to find in the trigram index for the operations verifying
+/// only the candidate words, with the predicate verifying them.
+pub(crate) fn trigram_match(direction: Direction, q: &WordQuery) -> Option<(&str, WordPredicate)> {
+    match (q.op, direction) {
+        (WordQueryOp::Contains, _) => Some((q.directional_word(direction), |w, q| w.contains(q))),
+        (WordQueryOp::EndsWith, Direction::Forward) => Some((&q.word, |w, q| w.ends_with(q))),
+        _ => None,
+    }
 }
 
-fn starts_with<'a>(
+fn starts_with<'a, R: WordRows<'a>>(
     direction: Direction,
-    word_index: &'a WordIndex,
+    word_index: R,
     q: &WordQuery,
     out: &mut Vec<MatchEntry<'a>>,
 ) {
@@ -1,9 +1,140 @@
-use crate::{Index, IndexToQuery};
+use crate::{
+    frozen::{FrozenDocAttrRanges, FrozenDocAttrWords, FrozenIndex},
+    DocId, FuzzyPolicy, Index, IndexToQuery, MatchEntry, WordQuery,
+};
+use std::{ops::Range, slice};
 
 pub(crate) struct IndexResults<'a> {
-    pub(crate) index: &'a Index,
-    pub(crate) index_to_query: IndexToQuery<'a>,
+    pub(crate) index: IndexRef<'a>,
+    pub(crate) index_to_query: IndexToQuery,
 }
 
 unsafe impl<'a> Send for IndexResults<'a> {}
 unsafe impl<'a> Sync for IndexResults<'a> {}
+
+/// An index to query, owned by a [crate::Searcher] or read in place by a [crate::FrozenSearcher].
+#[derive(Clone, Copy)]
+pub(crate) enum IndexRef<'a> {
+    Frozen(FrozenIndex<'a>),
+    Live(&'a Index),
+}
+
+impl<'a> IndexRef<'a> {
+    /// Gets the byte ranges of the words of a doc attribute in its value.
+    pub(crate) fn get_doc_attribute_ranges(
+        self,
+        id: DocId,
+        attr_index: usize,
+    ) -> DocAttrRanges<'a> {
+        match self {
+            Self::Frozen(index) => {
+                DocAttrRanges::Frozen(index.get_doc_attribute_ranges(id, attr_index))
+            }
+            Self::Live(index) => {
+                DocAttrRanges::Live(index.get_doc_attribute_ranges(id, attr_index).iter())
+            }
+        }
+    }
+
+    pub(crate) fn get_doc_attribute_words(self, id: DocId, attr_index: usize) -> DocAttrWords<'a> {
+        match self {
+            Self::Frozen(index) => {
+                DocAttrWords::Frozen(index.get_doc_attribute_words(id, attr_index))
+            }
+            Self::Live(index) => {
+                DocAttrWords::Live(index.get_doc_attribute_words(id, attr_index).iter())
+            }
+        }
+    }
+
+    /// Checks if a word of a query is a stop word of a culture in a group.
+    pub(crate) fn is_stop_word(self, group: usize, culture: u8, word: &str) -> bool {
+        match self {
+            Self::Frozen(index) => index.is_stop_word(group, culture, word),
+            Self::Live(index) => index.is_stop_word(group, culture, word),
+        }
+    }
+
+    pub(crate) fn query(
+        self,
+        q: &WordQuery,
+        group: usize,
+        culture: u8,
+        policy: &FuzzyPolicy,
+        out: &mut Vec<MatchEntry<'a>>,
+    ) {
+        match self {
+            Self::Frozen(index) => index.query(q, group, culture, policy, out),
+            Self::Live(index) => index.query(q, group, culture, policy, out),
+        }
+    }
+
+    pub(crate) fn query_stop_word(
+        self,
+        q: &WordQuery,
+        group: usize,
+        culture: u8,
+        out: &mut Vec<MatchEntry<'a>>,
+    ) {
+        match self {
+            Self::Frozen(index) => index.query_stop_word(q, group, culture, out),
+            Self::Live(index) => index.query_stop_word(q, group, culture, out),
+        }
+    }
+}
+
+/// The byte ranges of the words of a doc attribute, in the order they appear.
+pub(crate) enum DocAttrRanges<'a> {
+    Frozen(FrozenDocAttrRanges<'a>),
+    Live(slice::Iter<'a, Range<u32>>),
+}
+
+impl<'a> Iterator for DocAttrRanges<'a> {
+    type Item = Range<usize>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        let range = match self {
+            Self::Frozen(iter) => iter.next()?,
+            Self::Live(iter) => iter.next()?.clone(),
+        };
+
+        Some(range.start as usize..range.end as usize)
+    }
+}
+
+/// The interned words of a doc attribute, in the order they appear.
+#[derive(Clone)]
+pub(crate) enum DocAttrWords<'a> {
+    Frozen(FrozenDocAttrWords<'a>),
+    Live(slice::Iter<'a, *const str>),
+}
+
+impl<'a> DocAttrWords<'a> {
+    pub(crate) fn empty() -> Self {
+        Self::Live([].iter())
+    }
+
+    pub(crate) fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+}
+
+impl<'a> ExactSizeIterator for DocAttrWords<'a> {}
+
+impl<'a> Iterator for DocAttrWords<'a> {
+    type Item = *const str;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        match self {
+            Self::Frozen(iter) => iter.next(),
+            Self::Live(iter) => iter.next().copied(),
+        }
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        match self {
+            Self::Frozen(iter) => iter.size_hint(),
+            Self::Live(iter) => iter.size_hint(),
+        }
+    }
+}
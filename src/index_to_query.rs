@@ -1,34 +1,31 @@
 use crate::{match_entry::MatchEntry, MatchDistance, WordQuery};
 use fxhash::FxHashMap;
-use roaring::RoaringBitmap;
 use std::{cmp::max, collections::hash_map::Entry};
 
 /// A reverse index to find WordQuery from indexed word.
 ///
 /// Words that have the best
 #[derive(Default)]
-pub(crate) struct IndexToQuery<'a> {
-    map: FxHashMap<*const str, IndexToQueryEntry<'a>>,
+pub(crate) struct IndexToQuery {
+    map: FxHashMap<*const str, IndexToQueryEntry>,
     query_len: usize,
 }
 
-impl<'a> IndexToQuery<'a> {
+impl IndexToQuery {
     /// Add a match entry associated with a query, keeping only the best matches.
-    pub(crate) fn add(&mut self, query: &WordQuery, match_entry: MatchEntry<'a>) {
+    pub(crate) fn add(&mut self, query: &WordQuery, match_entry: MatchEntry) {
         match self.map.entry(match_entry.word) {
             Entry::Occupied(mut o) => {
                 let o = o.get_mut();
 
                 if (o.distance, o.query_index) > (match_entry.distance, query.index) {
                     o.distance = match_entry.distance;
-                    o.docs = match_entry.docs;
                     o.query_index = query.index;
                 }
             }
             Entry::Vacant(v) => {
                 v.insert(IndexToQueryEntry {
                     distance: match_entry.distance,
-                    docs: match_entry.docs,
                     query_index: query.index,
                 });
             }
@@ -37,7 +34,7 @@ impl<'a> IndexToQuery<'a> {
         self.query_len = max(self.query_len, query.index + 1);
     }
 
-    pub(crate) fn extend<I: IntoIterator<Item = MatchEntry<'a>>>(
+    pub(crate) fn extend<'a, I: IntoIterator<Item = MatchEntry<'a>>>(
         &mut self,
         query: &WordQuery,
         it: I,
@@ -47,7 +44,7 @@ impl<'a> IndexToQuery<'a> {
         }
     }
 
-    pub(crate) fn get(&self, word: *const str) -> Option<&IndexToQueryEntry<'a>> {
+    pub(crate) fn get(&self, word: *const str) -> Option<&IndexToQueryEntry> {
         self.map.get(&word)
     }
 
@@ -56,8 +53,7 @@ impl<'a> IndexToQuery<'a> {
     }
 }
 
-pub(crate) struct IndexToQueryEntry<'a> {
+pub(crate) struct IndexToQueryEntry {
     pub(crate) distance: MatchDistance,
-    pub(crate) docs: &'a RoaringBitmap,
     pub(crate) query_index: usize,
 }
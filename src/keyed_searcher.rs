@@ -0,0 +1,406 @@
+use crate::{
+    binary::{
+        read_bool, read_header, read_len, read_str, read_u32, write_bool, write_header, write_len,
+        write_str, write_u32,
+    },
+    AttrProps, DocId, Normalizer, SearchQuery, SearchResults, Searcher, Tokenizer,
+};
+use fxhash::FxHashMap;
+use std::{
+    borrow::Borrow,
+    hash::Hash,
+    io::{self, Read, Write},
+    sync::Arc,
+};
+use uuid::Uuid;
+
+const SNAPSHOT_MAGIC: &[u8; 4] = b"SRCK";
+const SNAPSHOT_VERSION: u32 = 1;
+
+/// A searcher of docs identified by [Uuid].
+pub type UuidSearcher = KeyedSearcher<Uuid>;
+
+/// A searcher of docs identified by a key, allocating the [DocId] of each key.
+///
+/// The [DocId] of a removed doc is reused by the next inserted key.
+pub struct KeyedSearcher<K> {
+    doc_ids: FxHashMap<K, DocId>,
+    free_ids: Vec<DocId>,
+    keys: Vec<Option<K>>,
+    searcher: Searcher,
+}
+
+impl<K: Clone + Eq + Hash> KeyedSearcher<K> {
+    pub fn new() -> Self {
+        Self {
+            doc_ids: Default::default(),
+            free_ids: Vec::new(),
+            keys: Vec::new(),
+            searcher: Searcher::new(),
+        }
+    }
+
+    /// Gets the [DocId] allocated to a key.
+    pub fn doc_id<Q>(&self, key: &Q) -> Option<DocId>
+    where
+        K: Borrow<Q>,
+        Q: Eq + Hash + ?Sized,
+    {
+        self.doc_ids.get(key).copied()
+    }
+
+    fn ensure_doc_id(&mut self, key: &K) -> DocId {
+        if let Some(id) = self.doc_ids.get(key) {
+            return *id;
+        }
+
+        let id = match self.free_ids.pop() {
+            Some(id) => {
+                self.keys[id.index()] = Some(key.clone());
+                id
+            }
+            None => {
+                let id = DocId::from(self.keys.len() as u32);
+                self.keys.push(Some(key.clone()));
+                id
+            }
+        };
+
+        self.doc_ids.insert(key.clone(), id);
+        id
+    }
+
+    /// Gets the words inside a doc attribute as they are indexed.
+    pub fn get_doc_attr_words<'a, Q>(&'a self, key: &Q, name: &str) -> impl Iterator<Item = &'a str>
+    where
+        K: Borrow<Q>,
+        Q: Eq + Hash + ?Sized,
+    {
+        self.doc_id(key)
+            .map(|id| self.searcher.get_doc_attr_words(id, name))
+            .into_iter()
+            .flatten()
+    }
+
+    pub fn insert_doc_attribute(&mut self, key: &K, name: &str, value: &str) {
+        // an id is only allocated for a new key with words to index.
+        let is_new = !self.doc_ids.contains_key(key);
+
+        if is_new && !self.searcher.has_words(name, value) {
+            return;
+        }
+
+        let id = self.ensure_doc_id(key);
+        self.searcher.insert_doc_attribute(id, name, value);
+    }
+
+    /// Gets the key of a [DocId].
+    pub fn key(&self, doc_id: DocId) -> Option<&K> {
+        self.keys.get(doc_id.index())?.as_ref()
+    }
+
+    pub fn query<'a>(&'a self, query: &SearchQuery) -> KeyedSearchResults<'a, K> {
+        KeyedSearchResults {
+            results: self.searcher.query(query),
+            searcher: self,
+        }
+    }
+
+    pub fn register_normalizer(&mut self, name: &str, normalizer: Arc<dyn Normalizer>) {
+        self.searcher.register_normalizer(name, normalizer);
+    }
+
+    pub fn register_tokenizer(&mut self, name: &str, tokenizer: Arc<dyn Tokenizer>) {
+        self.searcher.register_tokenizer(name, tokenizer);
+    }
+
+    pub fn remove_attr(&mut self, name: &str) -> bool {
+        self.searcher.remove_attr(name)
+    }
+
+    /// Removes the doc of a key, its [DocId] is freed to be reused.
+    pub fn remove_doc<Q>(&mut self, key: &Q) -> bool
+    where
+        K: Borrow<Q>,
+        Q: Eq + Hash + ?Sized,
+    {
+        match self.doc_ids.remove(key) {
+            Some(id) => {
+                self.searcher.remove_doc(id);
+                self.keys[id.index()] = None;
+                self.free_ids.push(id);
+                true
+            }
+            None => false,
+        }
+    }
+
+    /// The underlying searcher, indexing the docs by their allocated [DocId].
+    pub fn searcher(&self) -> &Searcher {
+        &self.searcher
+    }
+
+    pub fn set_attribute(&mut self, name: String, attr: AttrProps) -> bool {
+        self.searcher.set_attribute(name, attr)
+    }
+
+    pub fn set_culture_normalizer(&mut self, culture: u8, normalizer: Arc<dyn Normalizer>) {
+        self.searcher.set_culture_normalizer(culture, normalizer);
+    }
+
+    pub fn set_culture_tokenizer(&mut self, culture: u8, tokenizer: Arc<dyn Tokenizer>) {
+        self.searcher.set_culture_tokenizer(culture, tokenizer);
+    }
+
+    pub fn set_stop_words<I, S>(&mut self, culture: u8, words: I)
+    where
+        I: IntoIterator<Item = S>,
+        S: AsRef<str>,
+    {
+        self.searcher.set_stop_words(culture, words);
+    }
+}
+
+impl<K: Clone + Eq + Hash + SnapshotKey> KeyedSearcher<K> {
+    /// Loads a searcher from a snapshot written by [KeyedSearcher::save_to],
+    /// the searcher with the keys of its docs.
+    ///
+    /// As for [Searcher::load_from], the tokenizers and normalizers must be
+    /// registered again.
+    pub fn load_from<R: Read>(mut reader: R) -> io::Result<Self> {
+        let r = &mut reader;
+
+        read_header(r, SNAPSHOT_MAGIC, SNAPSHOT_VERSION)?;
+
+        let searcher = Searcher::load_from(&mut *r)?;
+        let mut doc_ids = FxHashMap::default();
+        let mut keys = Vec::new();
+
+        for index in 0..read_len(r)? {
+            let key = match read_bool(r)? {
+                true => Some(K::read_key(r)?),
+                false => None,
+            };
+
+            if let Some(key) = &key {
+                doc_ids.insert(key.clone(), DocId::from(index as u32));
+            }
+
+            keys.push(key);
+        }
+
+        let free_ids = (0..read_len(r)?)
+            .map(|_| read_u32(r).map(DocId::from))
+            .collect::<io::Result<_>>()?;
+
+        Ok(Self {
+            doc_ids,
+            free_ids,
+            keys,
+            searcher,
+        })
+    }
+
+    /// Writes a snapshot of the searcher and of the keys of its docs, that
+    /// can be loaded back with [KeyedSearcher::load_from].
+    ///
+    /// The writer receives many small writes, wrap it in a [std::io::BufWriter]
+    /// when writing to a file.
+    pub fn save_to<W: Write>(&self, mut writer: W) -> io::Result<()> {
+        let w = &mut writer;
+
+        write_header(w, SNAPSHOT_MAGIC, SNAPSHOT_VERSION)?;
+        self.searcher.save_to(&mut *w)?;
+        write_len(w, self.keys.len())?;
+
+        for key in &self.keys {
+            write_bool(w, key.is_some())?;
+
+            if let Some(key) = key {
+                key.write_key(w)?;
+            }
+        }
+
+        write_len(w, self.free_ids.len())?;
+        self.free_ids.iter().try_for_each(|id| write_u32(w, id.0))?;
+        w.flush()
+    }
+}
+
+impl<K: Clone + Eq + Hash> Default for KeyedSearcher<K> {
+    #[inline]
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+/// A key saved in the snapshots of a [KeyedSearcher].
+pub trait SnapshotKey: Sized {
+    fn read_key<R: Read>(r: &mut R) -> io::Result<Self>;
+
+    fn write_key<W: Write>(&self, w: &mut W) -> io::Result<()>;
+}
+
+impl SnapshotKey for String {
+    fn read_key<R: Read>(r: &mut R) -> io::Result<Self> {
+        read_str(r).map(String::from)
+    }
+
+    fn write_key<W: Write>(&self, w: &mut W) -> io::Result<()> {
+        write_str(w, self)
+    }
+}
+
+impl SnapshotKey for u32 {
+    fn read_key<R: Read>(r: &mut R) -> io::Result<Self> {
+        read_u32(r)
+    }
+
+    fn write_key<W: Write>(&self, w: &mut W) -> io::Result<()> {
+        write_u32(w, *self)
+    }
+}
+
+impl SnapshotKey for Uuid {
+    fn read_key<R: Read>(r: &mut R) -> io::Result<Self> {
+        let mut bytes = [0; 16];
+        r.read_exact(&mut bytes)?;
+        Ok(Uuid::from_bytes(bytes))
+    }
+
+    fn write_key<W: Write>(&self, w: &mut W) -> io::Result<()> {
+        w.write_all(self.as_bytes())
+    }
+}
+
+pub struct KeyedSearchResults<'a, K> {
+    results: SearchResults<'a>,
+    searcher: &'a KeyedSearcher<K>,
+}
+
+impl<'a, K: Clone + Eq + Hash> KeyedSearchResults<'a, K> {
+    pub fn contains_key<Q>(&self, key: &Q) -> bool
+    where
+        K: Borrow<Q>,
+        Q: Eq + Hash + ?Sized,
+    {
+        self.searcher
+            .doc_id(key)
+            .is_some_and(|id| self.results.contains_doc_id(id))
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.results.is_empty()
+    }
+
+    /// Iterates the keys of the docs found, in the order of their [DocId].
+    pub fn keys(&self) -> impl Iterator<Item = &'a K> + '_ {
+        self.results.iter().filter_map(|id| self.searcher.key(id))
+    }
+
+    /// Gets the number of docs found.
+    pub fn len(&self) -> usize {
+        self.results.len()
+    }
+
+    /// The results by [DocId], to compare docs or get their matched words.
+    pub fn results(&self) -> &SearchResults<'a> {
+        &self.results
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn insert_query_remove() {
+        let mut searcher = KeyedSearcher::<String>::new();
+        searcher.set_attribute("*".into(), AttrProps::default());
+
+        searcher.insert_doc_attribute(&"a".into(), "*", "balance echue");
+        searcher.insert_doc_attribute(&"b".into(), "*", "balance courante");
+        searcher.insert_doc_attribute(&"c".into(), "*", "valeur courante");
+
+        assert_eq!(searcher.doc_id("b"), Some(DocId::from(1)));
+
+        let results = searcher.query(&SearchQuery::new(0, "balance"));
+
+        assert!(results.contains_key("a"));
+        assert!(!results.contains_key("c"));
+        assert_eq!(results.keys().collect::<Vec<_>>(), vec!["a", "b"]);
+
+        assert!(searcher.remove_doc("a"));
+        assert!(!searcher.remove_doc("a"));
+        assert_eq!(searcher.key(DocId::from(0)), None);
+
+        searcher.insert_doc_attribute(&"d".into(), "*", "balance");
+
+        assert_eq!(searcher.doc_id("d"), Some(DocId::from(0)));
+        assert_eq!(
+            searcher.get_doc_attr_words("d", "*").collect::<Vec<_>>(),
+            vec!["balance"]
+        );
+
+        let results = searcher.query(&SearchQuery::new(0, "balance"));
+        assert_eq!(results.keys().collect::<Vec<_>>(), vec!["d", "b"]);
+    }
+
+    #[test]
+    fn insert_without_id() {
+        let mut searcher = KeyedSearcher::<String>::new();
+        searcher.set_attribute("*".into(), AttrProps::default());
+
+        searcher.insert_doc_attribute(&"a".into(), "unknown", "balance");
+        searcher.insert_doc_attribute(&"b".into(), "*", "");
+        searcher.insert_doc_attribute(&"e".into(), "*", "  ,, ");
+
+        assert_eq!(searcher.doc_id("a"), None);
+        assert_eq!(searcher.doc_id("b"), None);
+        assert_eq!(searcher.doc_id("e"), None);
+
+        searcher.insert_doc_attribute(&"c".into(), "*", "balance");
+
+        assert_eq!(searcher.doc_id("c"), Some(DocId::from(0)));
+    }
+
+    #[test]
+    fn save_load() {
+        let mut searcher = KeyedSearcher::<String>::new();
+        searcher.set_attribute("*".into(), AttrProps::default());
+
+        searcher.insert_doc_attribute(&"a".into(), "*", "balance echue");
+        searcher.insert_doc_attribute(&"b".into(), "*", "balance courante");
+        searcher.insert_doc_attribute(&"c".into(), "*", "valeur courante");
+        searcher.remove_doc("a");
+
+        let mut buf = Vec::new();
+        searcher.save_to(&mut buf).unwrap();
+
+        let mut searcher = KeyedSearcher::<String>::load_from(&buf[..]).unwrap();
+
+        assert_eq!(searcher.doc_id("a"), None);
+        assert_eq!(searcher.doc_id("c"), Some(DocId::from(2)));
+        assert_eq!(searcher.key(DocId::from(1)).map(String::as_str), Some("b"));
+
+        let results = searcher.query(&SearchQuery::new(0, "courante"));
+        assert_eq!(results.keys().collect::<Vec<_>>(), vec!["b", "c"]);
+
+        // the freed id is still reused.
+        searcher.insert_doc_attribute(&"d".into(), "*", "balance");
+        assert_eq!(searcher.doc_id("d"), Some(DocId::from(0)));
+    }
+
+    #[test]
+    fn uuid_keys() {
+        let mut searcher = UuidSearcher::new();
+        searcher.set_attribute("*".into(), AttrProps::default());
+
+        let id = Uuid::from_u128(42);
+        searcher.insert_doc_attribute(&id, "*", "country");
+
+        assert!(searcher
+            .query(&SearchQuery::new(0, "country"))
+            .contains_key(&id));
+    }
+}
@@ -1,39 +1,75 @@
+mod analyzer;
 mod attr_props;
+mod binary;
+mod chunked_vec;
 mod comparers;
+mod dfa_cache;
 mod direction;
 mod doc_id;
+mod frozen;
+mod fuzzy_policy;
 mod index;
 //mod index_old;
 mod index_results;
 mod index_to_query;
+mod keyed_searcher;
 mod match_distance;
 mod match_entry;
 mod min_match_level;
+mod normalizer;
+mod operation;
+mod operation_log;
 mod presence;
+mod ranking;
+mod replication;
 mod search_query;
 mod search_results;
 mod searcher;
+mod shared_searcher;
+mod stop_words;
+mod stored_values;
 mod str_intern;
+mod token_pattern;
+mod tokenizer;
+mod transaction;
+mod trigram_index;
 mod word_index;
 mod word_query;
 mod word_query_op;
 
+use analyzer::{Analyzer, Analyzers};
 pub use attr_props::AttrProps;
-pub use comparers::{compare, Comparer};
+use chunked_vec::ChunkedVec;
+use comparers::BucketSort;
+pub use comparers::{compare, Comparer, RankKey};
 pub use direction::Direction;
 pub use doc_id::DocId;
+pub use frozen::FrozenSearcher;
+pub use fuzzy_policy::FuzzyPolicy;
 use index::{Index, IndexLog};
-use index_results::IndexResults;
+use index_results::{IndexRef, IndexResults};
 use index_to_query::IndexToQuery;
+pub use keyed_searcher::{KeyedSearchResults, KeyedSearcher, SnapshotKey, UuidSearcher};
 use match_distance::MatchDistance;
 use match_entry::MatchEntry;
 pub use min_match_level::MinMatchLevel;
+pub use normalizer::{Normalizer, StandardNormalizer};
+pub use operation::{Operation, OperationError};
+pub use operation_log::{LogFile, OperationLog, RejectedRecord};
 use presence::Presence;
+pub use ranking::SortedDocIds;
+pub use replication::{ReplicationEntry, ReplicationError, ReplicationFollower, ReplicationLeader};
+pub use roaring::RoaringBitmap;
 pub use search_query::SearchQuery;
 pub use search_results::SearchResults;
 use searcher::AttrMap;
 pub use searcher::Searcher;
+pub use shared_searcher::SharedSearcher;
+use stored_values::StoredValues;
 use str_intern::StrIntern;
+pub use token_pattern::TokenPattern;
+pub use tokenizer::{DefaultTokenizer, IdentifierTokenizer, Tokenizer};
+pub use transaction::Transaction;
 use word_index::{WordIndex, WordInternResolver};
 use word_query::WordQuery;
 use word_query_op::WordQueryOp;
@@ -1,11 +1,14 @@
-use crate::MatchDistance;
+use crate::{frozen::FrozenBitmap, MatchDistance};
 use roaring::RoaringBitmap;
-use std::fmt::{self, Debug, Formatter};
+use std::{
+    borrow::Cow,
+    fmt::{self, Debug, Formatter},
+};
 
 /// An entry matched during a query.
 pub(crate) struct MatchEntry<'a> {
     pub distance: MatchDistance,
-    pub docs: &'a RoaringBitmap,
+    pub docs: MatchDocs<'a>,
     pub word: &'a str,
 }
 
@@ -23,3 +26,43 @@ impl<'a> PartialEq<(MatchDistance, &str)> for MatchEntry<'a> {
         self.distance == other.0 && self.word == other.1
     }
 }
+
+/// The docs of a matched word, the bitmap of a live index or the serialized
+/// bitmap of a frozen one, read in place.
+pub(crate) enum MatchDocs<'a> {
+    Bitmap(Cow<'a, RoaringBitmap>),
+    Frozen(FrozenBitmap<'a>),
+}
+
+impl MatchDocs<'_> {
+    /// Keeps only the docs of `docs` in common with these.
+    pub(crate) fn intersect_into(&self, docs: &mut RoaringBitmap) {
+        match self {
+            Self::Bitmap(bitmap) => *docs &= &**bitmap,
+            Self::Frozen(bitmap) => bitmap.intersect_into(docs),
+        }
+    }
+
+    /// Adds these docs to `docs`.
+    pub(crate) fn union_into(&self, docs: &mut RoaringBitmap) {
+        match self {
+            Self::Bitmap(bitmap) => *docs |= &**bitmap,
+            Self::Frozen(bitmap) => bitmap.union_into(docs),
+        }
+    }
+
+    /// Adds the docs of another entry of the same word, these docs being
+    /// copied the first time.
+    pub(crate) fn union_with(&mut self, other: &MatchDocs) {
+        if let Self::Frozen(bitmap) = self {
+            let mut docs = RoaringBitmap::new();
+
+            bitmap.union_into(&mut docs);
+            *self = Self::Bitmap(Cow::Owned(docs));
+        }
+
+        if let Self::Bitmap(bitmap) = self {
+            other.union_into(bitmap.to_mut());
+        }
+    }
+}
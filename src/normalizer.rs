@@ -0,0 +1,163 @@
+use std::{borrow::Cow, ops::Range};
+use str_utils::char_map::lower_no_accent_char;
+use unicode_normalization::UnicodeNormalization;
+
+/// Normalizes the words split by a [Tokenizer](crate::Tokenizer), when the
+/// values are indexed and when the queries are parsed, so both produce the
+/// same words.
+pub trait Normalizer: Send + Sync {
+    /// Appends the normalized form of `word` to `out`, a word normalized to
+    /// nothing is skipped.
+    fn normalize(&self, word: &str, out: &mut String);
+}
+
+/// Lowercases the words and, when enabled, applies in order the Unicode NFKC
+/// normalization, the folding of the full-width forms, the expansion of the
+/// ligatures and the removal of the accents.
+///
+/// The default one only removes the accents, merging `ñ` with `n`; use
+/// [StandardNormalizer::lowercase] for the cultures where they are distinct.
+#[derive(Clone, Copy, Debug, Eq, Hash, PartialEq)]
+pub struct StandardNormalizer {
+    fold_accents: bool,
+    fold_full_width: bool,
+    ligatures: bool,
+    nfkc: bool,
+}
+
+impl StandardNormalizer {
+    /// Only lowercases the words.
+    pub const fn lowercase() -> Self {
+        Self {
+            fold_accents: false,
+            fold_full_width: false,
+            ligatures: false,
+            nfkc: false,
+        }
+    }
+
+    /// Removes the accents, `é` becoming `e`.
+    pub const fn fold_accents(mut self, fold_accents: bool) -> Self {
+        self.fold_accents = fold_accents;
+        self
+    }
+
+    /// Replaces the full-width forms by their ASCII equivalent, `Ａ１` becoming
+    /// `a1`.
+    pub const fn fold_full_width(mut self, fold_full_width: bool) -> Self {
+        self.fold_full_width = fold_full_width;
+        self
+    }
+
+    /// Expands the ligatures, `œ` becoming `oe` and `ß` becoming `ss`.
+    pub const fn ligatures(mut self, ligatures: bool) -> Self {
+        self.ligatures = ligatures;
+        self
+    }
+
+    /// Applies the Unicode NFKC normalization first, composing the accents and
+    /// replacing the compatibility chars, `ﬁ` becoming `fi` and `²` becoming
+    /// `2`.
+    pub const fn nfkc(mut self, nfkc: bool) -> Self {
+        self.nfkc = nfkc;
+        self
+    }
+
+    fn push_char(&self, c: char, out: &mut String) {
+        match self.fold_accents {
+            true => out.extend(lower_no_accent_char(c)),
+            false => out.extend(c.to_lowercase()),
+        }
+    }
+}
+
+impl Default for StandardNormalizer {
+    fn default() -> Self {
+        DEFAULT_NORMALIZER
+    }
+}
+
+impl Normalizer for StandardNormalizer {
+    fn normalize(&self, word: &str, out: &mut String) {
+        let word = match self.nfkc {
+            true => Cow::Owned(word.nfkc().collect::<String>()),
+            false => Cow::Borrowed(word),
+        };
+
+        for c in word.chars() {
+            let c = match self.fold_full_width {
+                true => fold_full_width(c),
+                false => c,
+            };
+
+            match ligature(c).filter(|_| self.ligatures) {
+                Some(s) => s.chars().for_each(|c| self.push_char(c, out)),
+                None => self.push_char(c, out),
+            }
+        }
+    }
+}
+
+pub(crate) static DEFAULT_NORMALIZER: StandardNormalizer =
+    StandardNormalizer::lowercase().fold_accents(true);
+
+/// The full-width forms of the ASCII chars.
+const FULL_WIDTH: Range<u32> = 0xFF01..0xFF5F;
+
+/// The offset from a full-width form to its ASCII char.
+const FULL_WIDTH_OFFSET: u32 = 0xFEE0;
+
+fn fold_full_width(c: char) -> char {
+    match c {
+        '\u{3000}' => ' ',
+        c if FULL_WIDTH.contains(&(c as u32)) => {
+            char::from_u32(c as u32 - FULL_WIDTH_OFFSET).unwrap_or(c)
+        }
+        c => c,
+    }
+}
+
+fn ligature(c: char) -> Option<&'static str> {
+    Some(match c {
+        'æ' => "ae",
+        'Æ' => "AE",
+        'œ' => "oe",
+        'Œ' => "OE",
+        'ß' => "ss",
+        'ẞ' => "SS",
+        'ĳ' => "ij",
+        'Ĳ' => "IJ",
+        'ﬀ' => "ff",
+        'ﬁ' => "fi",
+        'ﬂ' => "fl",
+        'ﬃ' => "ffi",
+        'ﬄ' => "ffl",
+        'ﬅ' | 'ﬆ' => "st",
+        _ => return None,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn normalize(normalizer: StandardNormalizer, word: &str) -> String {
+        let mut out = String::new();
+        normalizer.normalize(word, &mut out);
+        out
+    }
+
+    #[test]
+    fn presets() {
+        let lowercase = StandardNormalizer::lowercase();
+
+        assert_eq!(normalize(lowercase, "Año"), "año");
+        assert_eq!(normalize(lowercase, "Straße"), "straße");
+        assert_eq!(normalize(StandardNormalizer::default(), "Año"), "ano");
+        assert_eq!(normalize(lowercase.ligatures(true), "Straße"), "strasse");
+        assert_eq!(normalize(lowercase.ligatures(true), "Œuvre"), "oeuvre");
+        assert_eq!(normalize(lowercase.fold_full_width(true), "Ａｂ１"), "ab1");
+        assert_eq!(normalize(lowercase.nfkc(true), "ﬁn²"), "fin2");
+        assert_eq!(normalize(lowercase.nfkc(true), "Cafe\u{301}"), "café");
+    }
+}
@@ -0,0 +1,240 @@
+use crate::{
+    binary::{
+        invalid_data, read_bool, read_direction, read_len, read_str, read_u32, read_u8, write_bool,
+        write_direction, write_len, write_str, write_u32, write_u8,
+    },
+    fuzzy_policy::{read_fuzzy_policy, write_fuzzy_policy},
+    AttrMap, AttrProps, DocId, Searcher,
+};
+use fxhash::FxHashMap;
+use std::{
+    error::Error,
+    fmt::{self, Display, Formatter},
+    io::{self, Read, Write},
+};
+
+/// A modification of a [crate::Searcher], applied with
+/// [crate::Searcher::apply] or staged in a [crate::Transaction].
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub enum Operation {
+    InsertDocAttribute {
+        doc_id: DocId,
+        name: Box<str>,
+        value: Box<str>,
+    },
+    RemoveAttr {
+        name: Box<str>,
+    },
+    RemoveDoc {
+        doc_id: DocId,
+    },
+    SetAttribute {
+        name: Box<str>,
+        props: AttrProps,
+    },
+    SetStopWords {
+        culture: u8,
+        words: Box<[Box<str>]>,
+    },
+}
+
+impl Operation {
+    pub(crate) fn read_from<R: Read>(r: &mut R) -> io::Result<Self> {
+        Ok(match read_u8(r)? {
+            0 => Self::InsertDocAttribute {
+                doc_id: DocId::from(read_u32(r)?),
+                name: read_str(r)?,
+                value: read_str(r)?,
+            },
+            1 => Self::RemoveAttr { name: read_str(r)? },
+            2 => Self::RemoveDoc {
+                doc_id: DocId::from(read_u32(r)?),
+            },
+            3 => Self::SetAttribute {
+                name: read_str(r)?,
+                props: AttrProps {
+                    culture: match read_bool(r)? {
+                        true => Some(read_u8(r)?),
+                        false => None,
+                    },
+                    direction: read_direction(r)?,
+                    fuzzy_policy: read_fuzzy_policy(r)?,
+                    normalizer: match read_bool(r)? {
+                        true => Some(read_str(r)?),
+                        false => None,
+                    },
+                    priority: read_u8(r)?,
+                    store: read_bool(r)?,
+                    tokenizer: match read_bool(r)? {
+                        true => Some(read_str(r)?),
+                        false => None,
+                    },
+                },
+            },
+            4 => Self::SetStopWords {
+                culture: read_u8(r)?,
+                words: (0..read_len(r)?)
+                    .map(|_| read_str(r))
+                    .collect::<io::Result<_>>()?,
+            },
+            v => return Err(invalid_data(format!("invalid operation {v}"))),
+        })
+    }
+
+    pub(crate) fn write_to<W: Write>(&self, w: &mut W) -> io::Result<()> {
+        match self {
+            Self::InsertDocAttribute {
+                doc_id,
+                name,
+                value,
+            } => {
+                write_u8(w, 0)?;
+                write_u32(w, doc_id.0)?;
+                write_str(w, name)?;
+                write_str(w, value)
+            }
+            Self::RemoveAttr { name } => {
+                write_u8(w, 1)?;
+                write_str(w, name)
+            }
+            Self::RemoveDoc { doc_id } => {
+                write_u8(w, 2)?;
+                write_u32(w, doc_id.0)
+            }
+            Self::SetAttribute { name, props } => {
+                write_u8(w, 3)?;
+                write_str(w, name)?;
+                write_bool(w, props.culture.is_some())?;
+
+                if let Some(culture) = props.culture {
+                    write_u8(w, culture)?;
+                }
+
+                write_direction(w, props.direction)?;
+                write_fuzzy_policy(w, props.fuzzy_policy.as_ref())?;
+                write_bool(w, props.normalizer.is_some())?;
+
+                if let Some(normalizer) = &props.normalizer {
+                    write_str(w, normalizer)?;
+                }
+
+                write_u8(w, props.priority)?;
+                write_bool(w, props.store)?;
+                write_bool(w, props.tokenizer.is_some())?;
+
+                match &props.tokenizer {
+                    Some(tokenizer) => write_str(w, tokenizer),
+                    None => Ok(()),
+                }
+            }
+            Self::SetStopWords { culture, words } => {
+                write_u8(w, 4)?;
+                write_u8(w, *culture)?;
+                write_len(w, words.len())?;
+                words.iter().try_for_each(|word| write_str(w, word))
+            }
+        }
+    }
+}
+
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub enum OperationError {
+    /// The attribute set is already defined.
+    AttributeExists(Box<str>),
+
+    /// The tokenizer or normalizer named by the attribute inserted is not
+    /// registered.
+    UnknownAnalyzer(Box<str>),
+
+    /// The attribute inserted or removed is not defined.
+    UnknownAttribute(Box<str>),
+}
+
+impl Display for OperationError {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::AttributeExists(name) => write!(f, "attribute {name} already exists"),
+            Self::UnknownAnalyzer(name) => write!(f, "unknown tokenizer or normalizer {name}"),
+            Self::UnknownAttribute(name) => write!(f, "unknown attribute {name}"),
+        }
+    }
+}
+
+impl Error for OperationError {}
+
+/// Gets the analyzer names of an attribute as it is after the operations
+/// checked so far, `None` when it is not defined.
+fn attr_names<'a>(
+    attrs: &'a AttrMap,
+    changed: &FxHashMap<&'a str, Option<AnalyzerNames<'a>>>,
+    name: &str,
+) -> Option<AnalyzerNames<'a>> {
+    match changed.get(name) {
+        Some(names) => *names,
+        None => attrs
+            .get(name)
+            .map(|a| (a.normalizer.as_deref(), a.tokenizer.as_deref())),
+    }
+}
+
+/// Reads operations written by [write_operations].
+pub(crate) fn read_operations<R: Read>(r: &mut R) -> io::Result<Vec<Operation>> {
+    (0..read_len(r)?).map(|_| Operation::read_from(r)).collect()
+}
+
+/// Writes a list of operations, prefixed by their count.
+pub(crate) fn write_operations<W: Write>(w: &mut W, operations: &[Operation]) -> io::Result<()> {
+    write_len(w, operations.len())?;
+    operations.iter().try_for_each(|op| op.write_to(w))
+}
+
+/// The names of the normalizer and the tokenizer of an attribute.
+type AnalyzerNames<'a> = (Option<&'a str>, Option<&'a str>);
+
+/// Checks the operations in order, each against the attributes as they are
+/// after the operations before it, returns the errors with the index of their
+/// operation.
+pub(crate) fn check_operations(
+    searcher: &Searcher,
+    operations: &[Operation],
+) -> Vec<(usize, OperationError)> {
+    let attrs = searcher.attrs();
+
+    // attributes set or removed by the operations checked so far.
+    let mut changed = FxHashMap::<&str, Option<AnalyzerNames>>::default();
+    let mut errors = Vec::new();
+
+    for (index, op) in operations.iter().enumerate() {
+        let names = |name: &str, changed: &_| attr_names(attrs, changed, name);
+
+        let error = match op {
+            Operation::InsertDocAttribute { name, .. } => match names(name, &changed) {
+                Some((normalizer, tokenizer)) => searcher
+                    .analyzers()
+                    .unknown_name(normalizer, tokenizer)
+                    .map(|name| OperationError::UnknownAnalyzer(name.into())),
+                None => Some(OperationError::UnknownAttribute(name.clone())),
+            },
+            Operation::RemoveAttr { name } => match names(name, &changed) {
+                Some(_) => {
+                    changed.insert(name, None);
+                    None
+                }
+                None => Some(OperationError::UnknownAttribute(name.clone())),
+            },
+            Operation::RemoveDoc { .. } | Operation::SetStopWords { .. } => None,
+            Operation::SetAttribute { name, props } => match names(name, &changed) {
+                Some(_) => Some(OperationError::AttributeExists(name.clone())),
+                None => {
+                    let names = (props.normalizer.as_deref(), props.tokenizer.as_deref());
+                    changed.insert(name, Some(names));
+                    None
+                }
+            },
+        };
+
+        errors.extend(error.map(|e| (index, e)));
+    }
+
+    errors
+}
@@ -0,0 +1,287 @@
+use crate::{
+    binary::{read_frame, read_header, write_frame, write_header},
+    operation::{read_operations, write_operations},
+    Operation, OperationError, Searcher,
+};
+use std::{
+    fs::{File, OpenOptions},
+    io::{self, Cursor, Read, Seek, SeekFrom, Write},
+    path::Path,
+};
+
+const MAGIC: &[u8; 4] = b"SRCL";
+const VERSION: u32 = 5;
+
+/// The size of the magic bytes and version.
+const HEADER_LEN: u64 = 8;
+
+/// An append-only log of the operations applied to a [Searcher] since its last
+/// snapshot, replayed on startup on top of that snapshot.
+///
+/// Each record holds the operations of a commit, framed by its length and a
+/// CRC-32 checksum; a record is replayed all together or not at all. A torn or
+/// corrupted tail, left by a crash while appending, is truncated on replay.
+///
+/// A record the searcher rejects on replay is kept in the log and listed by
+/// [OperationLog::rejected], to be checked before the log is cleared.
+pub struct OperationLog<F = File> {
+    file: F,
+    rejected: Vec<RejectedRecord>,
+}
+
+impl OperationLog<File> {
+    /// Opens or creates the log file and replays it into the searcher.
+    pub fn open<P: AsRef<Path>>(path: P, searcher: &mut Searcher) -> io::Result<Self> {
+        let file = OpenOptions::new()
+            .create(true)
+            .truncate(false)
+            .read(true)
+            .write(true)
+            .open(path)?;
+
+        Self::replay(file, searcher)
+    }
+}
+
+impl<F: LogFile> OperationLog<F> {
+    /// Appends the operations as one record, synced before returning.
+    ///
+    /// The operations are appended before being applied: a record rejected by
+    /// [crate::Transaction::commit] is rejected the same way on replay.
+    pub fn append(&mut self, operations: &[Operation]) -> io::Result<()> {
+        let mut payload = Vec::new();
+
+        write_operations(&mut payload, operations)?;
+        write_frame(&mut self.file, &payload)?;
+        self.file.flush()?;
+        self.file.sync()
+    }
+
+    /// Empties the log, once a snapshot including all its operations is saved.
+    pub fn clear(&mut self) -> io::Result<()> {
+        self.file.set_len(HEADER_LEN)?;
+        self.file.seek(SeekFrom::End(0))?;
+        self.file.sync()
+    }
+
+    pub fn into_inner(self) -> F {
+        self.file
+    }
+
+    /// The records not applied by the last replay, rejected by the searcher.
+    ///
+    /// A record rejected when it was committed is rejected again, but one
+    /// rejected since, as when a tokenizer or a normalizer was not registered
+    /// again before the replay, holds updates missing from the searcher.
+    pub fn rejected(&self) -> &[RejectedRecord] {
+        &self.rejected
+    }
+
+    /// Replays the records of a log into the searcher, the log being truncated
+    /// after the last valid record and ready to be appended.
+    ///
+    /// The records rejected by the searcher are listed by
+    /// [OperationLog::rejected].
+    pub fn replay(mut file: F, searcher: &mut Searcher) -> io::Result<Self> {
+        file.seek(SeekFrom::Start(0))?;
+
+        let mut bytes = Vec::new();
+        file.read_to_end(&mut bytes)?;
+
+        if bytes.is_empty() {
+            write_header(&mut file, MAGIC, VERSION)?;
+            file.sync()?;
+            return Ok(Self {
+                file,
+                rejected: Vec::new(),
+            });
+        }
+
+        let mut r = Cursor::new(&bytes[..]);
+        read_header(&mut r, MAGIC, VERSION)?;
+
+        let mut valid_len = r.position();
+        let mut rejected = Vec::new();
+
+        for index in 0.. {
+            let Some(operations) = read_record(&mut r) else {
+                break;
+            };
+
+            if let Err(errors) = searcher.apply_all(&operations) {
+                rejected.push(RejectedRecord {
+                    errors,
+                    index,
+                    operations,
+                });
+            }
+
+            valid_len = r.position();
+        }
+
+        if valid_len < bytes.len() as u64 {
+            file.set_len(valid_len)?;
+            file.sync()?;
+        }
+
+        file.seek(SeekFrom::Start(valid_len))?;
+        Ok(Self { file, rejected })
+    }
+}
+
+/// A record of the log not applied on replay.
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct RejectedRecord {
+    /// The errors with the index of their operation in the record.
+    pub errors: Vec<(usize, OperationError)>,
+
+    /// The position of the record in the log.
+    pub index: usize,
+    pub operations: Vec<Operation>,
+}
+
+/// Reads a record, `None` at the end of the log or on a torn or corrupted record.
+fn read_record(r: &mut Cursor<&[u8]>) -> Option<Vec<Operation>> {
+    let payload = read_frame(r).ok()??;
+    read_operations(&mut &payload[..]).ok()
+}
+
+/// A file an [OperationLog] can be kept in.
+pub trait LogFile: Read + Seek + Write {
+    fn set_len(&mut self, len: u64) -> io::Result<()>;
+
+    /// Makes the written bytes durable.
+    fn sync(&mut self) -> io::Result<()>;
+}
+
+impl LogFile for File {
+    fn set_len(&mut self, len: u64) -> io::Result<()> {
+        File::set_len(self, len)
+    }
+
+    fn sync(&mut self) -> io::Result<()> {
+        self.sync_data()
+    }
+}
+
+impl LogFile for Cursor<Vec<u8>> {
+    fn set_len(&mut self, len: u64) -> io::Result<()> {
+        self.get_mut().truncate(len as usize);
+        Ok(())
+    }
+
+    fn sync(&mut self) -> io::Result<()> {
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{binary::crc32, AttrProps, DocId, SearchQuery};
+
+    #[test]
+    fn checksum() {
+        assert_eq!(crc32(b"123456789"), 0xCBF4_3926);
+    }
+
+    #[test]
+    fn replay_truncates_corrupted_tail() {
+        let mut searcher = Searcher::new();
+        let mut log = OperationLog::replay(Cursor::new(Vec::new()), &mut searcher).unwrap();
+
+        log.append(&[
+            Operation::SetAttribute {
+                name: "*".into(),
+                props: AttrProps::default().store(true),
+            },
+            Operation::InsertDocAttribute {
+                doc_id: DocId::from(0),
+                name: "*".into(),
+                value: "balance echue".into(),
+            },
+        ])
+        .unwrap();
+
+        log.append(&[Operation::InsertDocAttribute {
+            doc_id: DocId::from(1),
+            name: "*".into(),
+            value: "balance courante".into(),
+        }])
+        .unwrap();
+
+        let mut bytes = log.into_inner().into_inner();
+        let valid_len = bytes.len();
+
+        // a record torn by a crash.
+        bytes.extend_from_slice(&[40, 0, 0, 0, 1, 2]);
+
+        let mut searcher = Searcher::new();
+        let log = OperationLog::replay(Cursor::new(bytes.clone()), &mut searcher).unwrap();
+
+        assert_eq!(log.into_inner().into_inner().len(), valid_len);
+        assert_eq!(searcher.query(&SearchQuery::new(0, "balance")).len(), 2);
+        assert_eq!(
+            searcher.get_doc_attr_value(DocId::from(1), "*"),
+            Some("balance courante")
+        );
+
+        // a corrupted last record.
+        let last = valid_len - 1;
+        bytes[last] ^= 0xFF;
+
+        let mut searcher = Searcher::new();
+        let mut log = OperationLog::replay(Cursor::new(bytes), &mut searcher).unwrap();
+
+        assert_eq!(searcher.query(&SearchQuery::new(0, "balance")).len(), 1);
+
+        log.clear().unwrap();
+        assert_eq!(log.into_inner().into_inner().len(), HEADER_LEN as usize);
+    }
+
+    #[test]
+    fn replay_reports_rejected() {
+        let mut searcher = Searcher::new();
+        let mut log = OperationLog::replay(Cursor::new(Vec::new()), &mut searcher).unwrap();
+        let insert = Operation::InsertDocAttribute {
+            doc_id: DocId::from(0),
+            name: "code".into(),
+            value: "AB-12".into(),
+        };
+
+        log.append(&[Operation::SetAttribute {
+            name: "code".into(),
+            props: AttrProps::default().tokenizer("identifier"),
+        }])
+        .unwrap();
+        log.append(std::slice::from_ref(&insert)).unwrap();
+
+        // the tokenizer is not registered again before the replay.
+        let bytes = log.into_inner().into_inner();
+        let mut searcher = Searcher::new();
+        let log = OperationLog::replay(Cursor::new(bytes.clone()), &mut searcher).unwrap();
+
+        assert_eq!(
+            log.rejected(),
+            [RejectedRecord {
+                errors: vec![(0, OperationError::UnknownAnalyzer("identifier".into()))],
+                index: 1,
+                operations: vec![insert],
+            }]
+        );
+
+        // the record is kept, replayed once the tokenizer is registered.
+        assert_eq!(log.into_inner().into_inner(), bytes);
+
+        let mut searcher = Searcher::new();
+        searcher.register_tokenizer(
+            "identifier",
+            std::sync::Arc::new(crate::IdentifierTokenizer),
+        );
+
+        let log = OperationLog::replay(Cursor::new(bytes), &mut searcher).unwrap();
+
+        assert!(log.rejected().is_empty());
+        assert_eq!(searcher.query(&SearchQuery::new(0, "ab-12")).len(), 1);
+    }
+}
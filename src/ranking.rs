@@ -0,0 +1,66 @@
+use crate::{BucketSort, DocId, SearchResults};
+use std::cmp::max;
+
+/// The docs of the results sorted lazily, each step sorting only the docs
+/// needed to fill a page, the next page sorting a page twice bigger.
+pub struct SortedDocIds<'a, 'b> {
+    next: usize,
+    sort: BucketSort<'a, 'b>,
+    sorted: usize,
+}
+
+impl<'a, 'b> SortedDocIds<'a, 'b> {
+    pub(crate) fn new(results: &'b SearchResults<'a>) -> Self {
+        Self {
+            next: 0,
+            sort: BucketSort::new(results),
+            sorted: 0,
+        }
+    }
+
+    /// Sorts the ids from `self.next` up to `end`, the ids before being
+    /// already in place or skipped.
+    fn sort_until(&mut self, end: usize) {
+        if end > self.sorted {
+            self.sort.sort_range(self.next..end);
+            self.sorted = end;
+        }
+    }
+}
+
+impl<'a, 'b> Iterator for SortedDocIds<'a, 'b> {
+    type Item = DocId;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        if self.next >= self.sorted {
+            self.sort_until(self.next + max(self.next, PAGE_SIZE));
+        }
+
+        let id = *self.sort.ids().get(self.next)?;
+        self.next += 1;
+        Some(id)
+    }
+
+    fn nth(&mut self, n: usize) -> Option<Self::Item> {
+        self.next = self.next.saturating_add(n);
+        self.sort_until(self.next.saturating_add(PAGE_SIZE));
+        self.next()
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        let len = self.sort.ids().len().saturating_sub(self.next);
+        (len, Some(len))
+    }
+}
+
+impl<'a, 'b> ExactSizeIterator for SortedDocIds<'a, 'b> {}
+
+const PAGE_SIZE: usize = 32;
+
+/// Gets a page of the docs sorted by relevance, refining only the buckets of
+/// docs overlapping the page.
+pub(crate) fn top(results: &SearchResults, offset: usize, limit: usize) -> Vec<DocId> {
+    BucketSort::new(results)
+        .sort_range(offset..offset.saturating_add(limit))
+        .to_vec()
+}
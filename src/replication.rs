@@ -0,0 +1,308 @@
+use crate::{
+    binary::{read_frame, read_header, read_u64, write_frame, write_header, write_u64},
+    operation::{read_operations, write_operations},
+    Operation, OperationError, Searcher,
+};
+use std::{
+    error::Error,
+    fmt::{self, Display, Formatter},
+    io::{self, Read, Write},
+    sync::mpsc::{channel, Receiver, Sender},
+};
+
+const SNAPSHOT_MAGIC: &[u8; 4] = b"SRCR";
+const SNAPSHOT_VERSION: u32 = 1;
+
+/// The operations of a commit on the leader, numbered in the order they were
+/// applied, the first commit being 1.
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct ReplicationEntry {
+    pub operations: Vec<Operation>,
+    pub sequence: u64,
+}
+
+impl ReplicationEntry {
+    /// Reads an entry written by [ReplicationEntry::write_to], `None` at the end
+    /// of the stream.
+    pub fn read_from<R: Read>(mut reader: R) -> io::Result<Option<Self>> {
+        let Some(payload) = read_frame(&mut reader)? else {
+            return Ok(None);
+        };
+
+        let r = &mut &payload[..];
+
+        Ok(Some(Self {
+            sequence: read_u64(r)?,
+            operations: read_operations(r)?,
+        }))
+    }
+
+    /// Writes the entry as a checksummed frame, to be sent through a pipe or a
+    /// socket.
+    pub fn write_to<W: Write>(&self, mut writer: W) -> io::Result<()> {
+        let mut payload = Vec::new();
+
+        write_u64(&mut payload, self.sequence)?;
+        write_operations(&mut payload, &self.operations)?;
+        write_frame(&mut writer, &payload)
+    }
+}
+
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub enum ReplicationError {
+    /// Entries were missed, the follower must be reloaded from a snapshot.
+    Gap { expected: u64, received: u64 },
+
+    /// An operation of the entry was rejected, the follower diverged from the
+    /// leader.
+    Rejected(Vec<(usize, OperationError)>),
+}
+
+impl Display for ReplicationError {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Gap { expected, received } => {
+                write!(f, "expected entry {expected}, received {received}")
+            }
+            Self::Rejected(errors) => write!(f, "{} operations rejected", errors.len()),
+        }
+    }
+}
+
+impl Error for ReplicationError {}
+
+/// Numbers the commits applied on the leader searcher and streams them to the
+/// followers.
+#[derive(Default)]
+pub struct ReplicationLeader {
+    followers: Vec<Sender<ReplicationEntry>>,
+    sequence: u64,
+}
+
+impl ReplicationLeader {
+    /// Starts after the commit `sequence`, 0 for a new searcher. A searcher
+    /// does not know its sequence, see [ReplicationLeader::save_snapshot] to
+    /// save them together.
+    pub fn new(sequence: u64) -> Self {
+        Self {
+            followers: Vec::new(),
+            sequence,
+        }
+    }
+
+    /// Loads a searcher and the leader at its sequence from a snapshot written
+    /// by [ReplicationLeader::save_snapshot].
+    pub fn load_snapshot<R: Read>(reader: R) -> io::Result<(Self, Searcher)> {
+        let (sequence, searcher) = read_snapshot(reader)?;
+        Ok((Self::new(sequence), searcher))
+    }
+
+    /// Writes a snapshot of the searcher with the sequence of the last commit,
+    /// to start the followers from with [ReplicationFollower::load_snapshot].
+    pub fn save_snapshot<W: Write>(&self, searcher: &Searcher, writer: W) -> io::Result<()> {
+        write_snapshot(writer, self.sequence, searcher)
+    }
+
+    /// Applies the operations atomically to the searcher and sends them to the
+    /// followers, returns the sequence of the entry.
+    ///
+    /// Nothing is applied nor sent if an operation is invalid.
+    pub fn commit(
+        &mut self,
+        searcher: &mut Searcher,
+        operations: Vec<Operation>,
+    ) -> Result<u64, Vec<(usize, OperationError)>> {
+        searcher.apply_all(&operations)?;
+        self.sequence += 1;
+
+        let entry = ReplicationEntry {
+            operations,
+            sequence: self.sequence,
+        };
+
+        // followers whose receiver is dropped are forgotten.
+        self.followers.retain(|f| f.send(entry.clone()).is_ok());
+
+        Ok(self.sequence)
+    }
+
+    /// The sequence of the last commit, to start a follower from a snapshot of
+    /// the searcher taken at this point.
+    pub fn sequence(&self) -> u64 {
+        self.sequence
+    }
+
+    /// Streams the entries committed from now on.
+    pub fn subscribe(&mut self) -> Receiver<ReplicationEntry> {
+        let (sender, receiver) = channel();
+        self.followers.push(sender);
+        receiver
+    }
+}
+
+/// Applies the entries streamed by a leader to a follower searcher, in order.
+pub struct ReplicationFollower {
+    sequence: u64,
+}
+
+impl ReplicationFollower {
+    /// Starts at the sequence of the leader snapshot the searcher was loaded from.
+    pub fn new(sequence: u64) -> Self {
+        Self { sequence }
+    }
+
+    /// Loads a searcher and the follower at its sequence from a snapshot written
+    /// by [ReplicationLeader::save_snapshot] or [ReplicationFollower::save_snapshot].
+    pub fn load_snapshot<R: Read>(reader: R) -> io::Result<(Self, Searcher)> {
+        let (sequence, searcher) = read_snapshot(reader)?;
+        Ok((Self::new(sequence), searcher))
+    }
+
+    /// Writes a snapshot of the searcher with the sequence of the last entry
+    /// applied.
+    pub fn save_snapshot<W: Write>(&self, searcher: &Searcher, writer: W) -> io::Result<()> {
+        write_snapshot(writer, self.sequence, searcher)
+    }
+
+    /// Applies an entry to the searcher, returns `false` for an entry already
+    /// applied.
+    pub fn apply(
+        &mut self,
+        searcher: &mut Searcher,
+        entry: &ReplicationEntry,
+    ) -> Result<bool, ReplicationError> {
+        if entry.sequence <= self.sequence {
+            return Ok(false);
+        }
+
+        if entry.sequence != self.sequence + 1 {
+            return Err(ReplicationError::Gap {
+                expected: self.sequence + 1,
+                received: entry.sequence,
+            });
+        }
+
+        searcher
+            .apply_all(&entry.operations)
+            .map_err(ReplicationError::Rejected)?;
+
+        self.sequence = entry.sequence;
+        Ok(true)
+    }
+
+    /// The sequence of the last entry applied.
+    pub fn sequence(&self) -> u64 {
+        self.sequence
+    }
+}
+
+/// Reads a sequence and the snapshot of the searcher at that sequence.
+fn read_snapshot<R: Read>(mut reader: R) -> io::Result<(u64, Searcher)> {
+    let r = &mut reader;
+
+    read_header(r, SNAPSHOT_MAGIC, SNAPSHOT_VERSION)?;
+
+    let sequence = read_u64(r)?;
+    Ok((sequence, Searcher::load_from(r)?))
+}
+
+/// Writes a sequence followed by a snapshot of the searcher, see
+/// [Searcher::save_to].
+fn write_snapshot<W: Write>(mut writer: W, sequence: u64, searcher: &Searcher) -> io::Result<()> {
+    let w = &mut writer;
+
+    write_header(w, SNAPSHOT_MAGIC, SNAPSHOT_VERSION)?;
+    write_u64(w, sequence)?;
+    searcher.save_to(w)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{AttrProps, DocId, SearchQuery};
+
+    fn insert(doc_id: u32, value: &str) -> Operation {
+        Operation::InsertDocAttribute {
+            doc_id: DocId::from(doc_id),
+            name: "*".into(),
+            value: value.into(),
+        }
+    }
+
+    #[test]
+    fn follow_channel_and_stream() {
+        let mut leader_searcher = Searcher::new();
+        let mut leader = ReplicationLeader::default();
+
+        leader
+            .commit(
+                &mut leader_searcher,
+                vec![Operation::SetAttribute {
+                    name: "*".into(),
+                    props: AttrProps::default(),
+                }],
+            )
+            .unwrap();
+
+        // a follower started from a snapshot of the leader, with its sequence.
+        let mut snapshot = Vec::new();
+        leader
+            .save_snapshot(&leader_searcher, &mut snapshot)
+            .unwrap();
+
+        let (mut follower, mut follower_searcher) =
+            ReplicationFollower::load_snapshot(&snapshot[..]).unwrap();
+
+        assert_eq!(follower.sequence(), 1);
+
+        let receiver = leader.subscribe();
+
+        leader
+            .commit(&mut leader_searcher, vec![insert(0, "balance echue")])
+            .unwrap();
+        leader
+            .commit(&mut leader_searcher, vec![insert(1, "balance courante")])
+            .unwrap();
+
+        assert!(leader
+            .commit(
+                &mut leader_searcher,
+                vec![Operation::RemoveAttr { name: "a".into() }]
+            )
+            .is_err());
+
+        let mut stream = Vec::new();
+
+        for entry in receiver.try_iter() {
+            assert!(follower.apply(&mut follower_searcher, &entry).unwrap());
+            entry.write_to(&mut stream).unwrap();
+        }
+
+        assert_eq!(follower.sequence(), 3);
+        assert_eq!(
+            follower_searcher
+                .query(&SearchQuery::new(0, "balance"))
+                .len(),
+            2
+        );
+
+        // the same entries read from a byte stream by another follower.
+        let mut r = &stream[..];
+        let (mut other, mut other_searcher) =
+            ReplicationFollower::load_snapshot(&snapshot[..]).unwrap();
+        let first = ReplicationEntry::read_from(&mut r).unwrap().unwrap();
+        let second = ReplicationEntry::read_from(&mut r).unwrap().unwrap();
+
+        assert_eq!(ReplicationEntry::read_from(&mut r).unwrap(), None);
+        assert_eq!(
+            other.apply(&mut other_searcher, &second),
+            Err(ReplicationError::Gap {
+                expected: 2,
+                received: 3
+            })
+        );
+        assert_eq!(other.apply(&mut other_searcher, &first), Ok(true));
+        assert_eq!(other.apply(&mut other_searcher, &first), Ok(false));
+        assert_eq!(other.apply(&mut other_searcher, &second), Ok(true));
+    }
+}
@@ -1,119 +1,218 @@
-use crate::{presence::Presence, word_query_op::WordQueryOp, MinMatchLevel, WordQuery};
-use std::{iter::Peekable, str::Chars};
-use str_utils::char_map::lower_no_accent_char;
+use crate::{
+    presence::Presence, tokenizer::positions, word_query_op::WordQueryOp, Analyzer, FuzzyPolicy,
+    MinMatchLevel, Normalizer, Tokenizer, WordQuery,
+};
 
 pub struct SearchQuery {
     pub(crate) culture: u8,
+    pub(crate) fuzzy_policy: FuzzyPolicy,
+    min_match_level: MinMatchLevel,
+    terms: Vec<Term>,
     pub(crate) words: Vec<WordQuery>,
 }
 
 impl SearchQuery {
+    /// Parses a query with the [DefaultTokenizer](crate::DefaultTokenizer)
+    /// and the default [StandardNormalizer](crate::StandardNormalizer), see
+    /// [Searcher::parse_query](crate::Searcher::parse_query) to use the ones
+    /// of the culture.
+    ///
+    /// The searchers split the terms again with the tokenizer and normalizer
+    /// of each attribute, these words are only used for the attributes naming
+    /// ones not registered.
     pub fn new(culture: u8, s: &str) -> Self {
-        let mut chars = s.chars().peekable();
-        let mut words = Vec::new();
+        let analyzer = Analyzer::default();
+        Self::parse_with(culture, s, analyzer.tokenizer, analyzer.normalizer)
+    }
 
-        while let Some(token) = parse_token(&mut chars, words.len()) {
-            if !token.word.is_empty() {
-                words.push(token);
-            }
+    /// Parses a query, splitting its terms into words with the tokenizer and
+    /// normalizer used for the values, so a term split in several words
+    /// matches them all.
+    ///
+    /// A term is prefixed with `+` when required and `-` when denied, and
+    /// wrapped in quotes to match its words exactly. A leading `*` matches the
+    /// ends of the words and a trailing one their starts.
+    pub fn parse_with(
+        culture: u8,
+        s: &str,
+        tokenizer: &dyn Tokenizer,
+        normalizer: &dyn Normalizer,
+    ) -> Self {
+        let analyzer = Analyzer {
+            normalizer,
+            tokenizer,
+        };
+        let mut terms = Vec::new();
+        let mut rest = s;
+
+        while let Some(term) = parse_term(&mut rest) {
+            terms.push(term);
         }
 
-        Self { culture, words }
+        let mut query = Self {
+            culture,
+            fuzzy_policy: FuzzyPolicy::default(),
+            min_match_level: MinMatchLevel::Fuzzy,
+            terms,
+            words: Vec::new(),
+        };
+
+        query.words = query.term_words(analyzer).into_iter().flatten().collect();
+        query
     }
 
     pub fn is_empty(&self) -> bool {
         self.words.is_empty()
     }
 
+    /// Sets the typos tolerated in the fuzzy words, for the attributes without
+    /// their own policy.
+    pub fn set_fuzzy_policy(&mut self, policy: FuzzyPolicy) {
+        self.fuzzy_policy = policy;
+    }
+
     /// Force the minimal level of matching.
     pub fn set_min_match_level(&mut self, level: MinMatchLevel) {
-        match level {
-            MinMatchLevel::Contains => {
-                self.words.iter_mut().for_each(|w| {
-                    if matches!(w.op, WordQueryOp::Fuzzy) {
-                        w.op = WordQueryOp::Contains;
-                    }
+        self.min_match_level = level;
+        self.words
+            .iter_mut()
+            .for_each(|w| w.op = min_match_op(w.op, level));
+    }
+
+    /// Splits each term in words with an analyzer, by term, the words being
+    /// numbered in order.
+    ///
+    /// The parts given after a whole token, at its position, are optional fuzzy
+    /// words, dropped when the term is quoted or denied, the stars and the
+    /// presence of the term applying to the whole token.
+    pub(crate) fn term_words(&self, analyzer: Analyzer) -> Vec<Vec<WordQuery>> {
+        let mut tokens = Vec::new();
+
+        self.terms
+            .iter()
+            .enumerate()
+            .scan(0, |count, (index, term)| {
+                tokens.clear();
+                analyzer.words(&term.text, &mut |word, range| {
+                    tokens.push((Box::<str>::from(word), range))
                 });
-            }
-            MinMatchLevel::Equal => self.words.iter_mut().for_each(|w| w.op = WordQueryOp::Eq),
-            MinMatchLevel::Fuzzy => {}
-        }
+
+                let positions = positions(tokens.iter().map(|t| t.1.clone())).collect::<Vec<_>>();
+                let last = positions.last().copied();
+                let mut words = Vec::new();
+
+                for (i, (word, _)) in tokens.drain(..).enumerate() {
+                    let position = positions[i];
+                    let part = i > 0 && positions[i - 1] == position;
+
+                    let (mut op, presence) = match part {
+                        true if term.op == WordQueryOp::Eq || term.presence == Presence::Denied => {
+                            continue
+                        }
+                        true => (WordQueryOp::Fuzzy, Presence::Optional),
+                        false => (term.op, term.presence),
+                    };
+
+                    if !part && i == 0 && term.leading_star {
+                        op = WordQueryOp::EndsWith;
+                    }
+
+                    if !part && Some(position) == last && term.trailing_star {
+                        op = match op {
+                            WordQueryOp::EndsWith => WordQueryOp::Contains,
+                            _ => WordQueryOp::StartsWith,
+                        };
+                    }
+
+                    words.push(WordQuery::new(
+                        word,
+                        min_match_op(op, self.min_match_level),
+                        presence,
+                        index,
+                        *count + words.len(),
+                    ));
+                }
+
+                *count += words.len();
+                Some(words)
+            })
+            .collect()
+    }
+
+    pub(crate) fn term_count(&self) -> usize {
+        self.terms.len()
     }
 }
 
-fn parse_token(chars: &mut Peekable<Chars>, index: usize) -> Option<WordQuery> {
+/// Gets the operation of a word at least matching at a level.
+fn min_match_op(op: WordQueryOp, level: MinMatchLevel) -> WordQueryOp {
+    match (level, op) {
+        (MinMatchLevel::Contains, WordQueryOp::Fuzzy) => WordQueryOp::Contains,
+        (MinMatchLevel::Equal, _) => WordQueryOp::Eq,
+        _ => op,
+    }
+}
+
+/// A term of a query, split in words by the tokenizer.
+struct Term {
+    leading_star: bool,
+    op: WordQueryOp,
+    presence: Presence,
+    text: Box<str>,
+    trailing_star: bool,
+}
+
+/// Parses the next term, advancing `s` after it.
+fn parse_term(s: &mut &str) -> Option<Term> {
     let mut presence = Presence::Optional;
-    let mut op = WordQueryOp::Fuzzy;
-    let mut text = String::new();
+    let mut leading_star = false;
+
+    *s = s.trim_start();
 
     loop {
+        let mut chars = s.chars();
+
         match chars.next()? {
             '+' => presence = Presence::Required,
             '-' => presence = Presence::Denied,
-            '*' => op = WordQueryOp::EndsWith,
+            '*' => leading_star = true,
             c if c == '\'' || c == '"' => {
-                take_until(chars, &mut text, |v| v == c);
-                chars.next(); // eat the last quote.
-                op = WordQueryOp::Eq;
-                break;
-            }
-            c if c.is_alphanumeric() => {
-                lower_no_accent_char(c).for_each(|c| text.push(c));
-                take_until(chars, &mut text, |c| !c.is_alphanumeric());
-                break;
+                let text = chars.as_str();
+                let len = text.find(c).unwrap_or(text.len());
+
+                *s = text.get(len + c.len_utf8()..).unwrap_or_default();
+
+                return Some(Term {
+                    leading_star: false,
+                    op: WordQueryOp::Eq,
+                    presence,
+                    text: text[..len].into(),
+                    trailing_star: false,
+                });
             }
             c if c.is_whitespace() => {
                 presence = Presence::Optional;
-                op = WordQueryOp::Fuzzy;
+                leading_star = false;
             }
-            _ => {}
+            _ => break,
         }
-    }
 
-    loop {
-        match chars.peek() {
-            Some('*') => {
-                chars.next();
-
-                if chars.peek().map_or(true, |c| c.is_whitespace()) {
-                    match op {
-                        WordQueryOp::Fuzzy => op = WordQueryOp::StartsWith,
-                        WordQueryOp::Contains | WordQueryOp::Eq | WordQueryOp::StartsWith => {}
-                        WordQueryOp::EndsWith => op = WordQueryOp::Contains,
-                    }
-                }
-
-                continue;
-            }
-
-            Some(c) => {
-                if c.is_alphanumeric() || c.is_whitespace() {
-                    break;
-                }
-            }
-
-            None => break,
-        }
-        chars.next();
+        *s = chars.as_str();
     }
 
-    Some(WordQuery::new(text.into_boxed_str(), op, presence, index))
-}
+    let len = s.find(char::is_whitespace).unwrap_or(s.len());
+    let text = &s[..len];
+    let trimmed = text.trim_end_matches('*');
 
-fn take_until<F>(chars: &mut Peekable<Chars>, s: &mut String, f: F)
-where
-    F: Fn(char) -> bool,
-{
-    while chars.peek().map_or(false, |c| !f(*c)) {
-        #[allow(clippy::unwrap_used)]
-        let c = chars.next().unwrap();
-
-        if c.is_alphanumeric() {
-            lower_no_accent_char(c).for_each(|c| s.push(c));
-        } else if s.chars().last().map_or(false, |c| !c.is_whitespace()) {
-            s.push(' ');
-        }
-    }
+    *s = &s[len..];
+
+    Some(Term {
+        leading_star,
+        op: WordQueryOp::Fuzzy,
+        presence,
+        text: trimmed.into(),
+        trailing_star: trimmed.len() < text.len(),
+    })
 }
 
 #[test]
@@ -148,3 +247,49 @@ fn multiple_words() {
         ]
     );
 }
+
+#[test]
+fn split_words() {
+    assert_eq!(
+        SearchQuery::new(0, "abc123 *Hello-World* \"o'neil\"").words,
+        vec![
+            ("abc", WordQueryOp::Fuzzy),
+            ("123", WordQueryOp::Fuzzy),
+            ("hello", WordQueryOp::EndsWith),
+            ("world", WordQueryOp::StartsWith),
+            ("o", WordQueryOp::Eq),
+            ("neil", WordQueryOp::Eq)
+        ]
+    );
+}
+
+#[test]
+fn protected_patterns() {
+    assert_eq!(
+        SearchQuery::new(0, "-\"John.Doe@example.com\" v1.2* *www.site.com").words,
+        vec![
+            ("john.doe@example.com", WordQueryOp::Eq),
+            ("v1.2", WordQueryOp::StartsWith),
+            ("v", WordQueryOp::Fuzzy),
+            ("1", WordQueryOp::Fuzzy),
+            ("2", WordQueryOp::Fuzzy),
+            ("www.site.com", WordQueryOp::EndsWith),
+            ("www", WordQueryOp::Fuzzy),
+            ("site", WordQueryOp::Fuzzy),
+            ("com", WordQueryOp::Fuzzy)
+        ]
+    );
+
+    let query = SearchQuery::new(0, "-john.doe@example.com");
+    assert_eq!(
+        query.words,
+        vec![("john.doe@example.com", WordQueryOp::Fuzzy)]
+    );
+    assert_eq!(query.words[0].presence, Presence::Denied);
+
+    let query = SearchQuery::new(0, "+v1.2");
+    assert_eq!(query.words[0].presence, Presence::Required);
+    assert!(query.words[1..]
+        .iter()
+        .all(|w| w.presence == Presence::Optional));
+}
@@ -1,30 +1,41 @@
-use crate::{Direction, DocId, IndexResults, IndexToQuery, Searcher};
+use crate::{
+    index_results::DocAttrWords, ranking, searcher::PriorityDirectionIndexes,
+    stored_values::StoredRef, tokenizer::positions, AttrMap, Direction, DocId, IndexResults,
+    IndexToQuery, RankKey, SortedDocIds,
+};
 use roaring::RoaringBitmap;
+use std::{
+    cmp::{min, Reverse},
+    ops::Range,
+};
 
 pub struct SearchResults<'a> {
+    attrs: &'a AttrMap,
+    pub(crate) attrs_priorities: &'a [PriorityDirectionIndexes],
     backward: IndexResults<'a>,
-    pub(crate) culture: u8,
 
     doc_ids: RoaringBitmap,
 
     forward: IndexResults<'a>,
-    pub(crate) searcher: &'a Searcher,
+    stored: Option<StoredRef<'a>>,
 }
 
 impl<'a> SearchResults<'a> {
     pub(crate) fn new(
+        attrs: &'a AttrMap,
+        attrs_priorities: &'a [PriorityDirectionIndexes],
         backward: IndexResults<'a>,
-        culture: u8,
         doc_ids: RoaringBitmap,
         forward: IndexResults<'a>,
-        searcher: &'a Searcher,
+        stored: Option<StoredRef<'a>>,
     ) -> Self {
         Self {
+            attrs,
+            attrs_priorities,
             backward,
-            culture,
             doc_ids,
             forward,
-            searcher,
+            stored,
         }
     }
 
@@ -32,6 +43,11 @@ impl<'a> SearchResults<'a> {
         self.doc_ids.contains(id.0)
     }
 
+    /// Gets the ids of the docs found.
+    pub fn doc_ids(&self) -> &RoaringBitmap {
+        &self.doc_ids
+    }
+
     pub(crate) fn direction_index_results(&self, direction: Direction) -> &IndexResults<'a> {
         match direction {
             Direction::Forward => &self.forward,
@@ -39,17 +55,170 @@ impl<'a> SearchResults<'a> {
         }
     }
 
+    /// Gets the stored value of a doc attribute with the matched words wrapped
+    /// between `pre_tag` and `post_tag`, fuzzy and prefix matches included.
+    ///
+    /// Returns `None` when the attribute is not stored.
+    pub fn highlight(
+        &self,
+        doc_id: DocId,
+        attr: &str,
+        pre_tag: &str,
+        post_tag: &str,
+    ) -> Option<String> {
+        let (value, words) = self.stored_value_words(doc_id, attr)?;
+        let mut out = String::with_capacity(value.len());
+
+        push_highlighted(
+            &mut out,
+            value,
+            0..value.len(),
+            &words,
+            Some((pre_tag, post_tag)),
+        );
+        Some(out)
+    }
+
+    /// Takes the ids of the docs found.
+    pub fn into_doc_ids(self) -> RoaringBitmap {
+        self.doc_ids
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.doc_ids.is_empty()
+    }
+
+    /// Iterates the ids of the docs found, in ascending order.
+    pub fn iter(&self) -> impl Iterator<Item = DocId> + '_ {
+        self.doc_ids.iter().map(DocId)
+    }
+
+    /// Gets the number of docs found.
+    pub fn len(&self) -> usize {
+        self.doc_ids.len() as usize
+    }
+
+    /// Computes the rank key of a doc found, keys compare as the docs compare with
+    /// [compare](crate::compare).
+    pub fn rank_key(&self, doc_id: DocId) -> Option<RankKey> {
+        self.contains_doc_id(doc_id)
+            .then(|| RankKey::new(doc_id, self))
+    }
+
+    /// Gets an excerpt of at most `max_words` words of the stored value of a doc
+    /// attribute, the window with the most distinct query words matched.
+    ///
+    /// The excerpt starts or ends with an ellipsis when the value is cropped, a
+    /// value without words is given trimmed. Returns `None` when the attribute
+    /// is not stored or `max_words` is 0.
+    pub fn snippet(&self, doc_id: DocId, attr: &str, max_words: usize) -> Option<String> {
+        self.snippet_impl(doc_id, attr, max_words, None)
+    }
+
+    /// Gets an excerpt as [SearchResults::snippet] with the matched words wrapped
+    /// between `pre_tag` and `post_tag`.
+    pub fn snippet_highlighted(
+        &self,
+        doc_id: DocId,
+        attr: &str,
+        max_words: usize,
+        pre_tag: &str,
+        post_tag: &str,
+    ) -> Option<String> {
+        self.snippet_impl(doc_id, attr, max_words, Some((pre_tag, post_tag)))
+    }
+
+    fn snippet_impl(
+        &self,
+        doc_id: DocId,
+        attr: &str,
+        max_words: usize,
+        tags: Option<(&str, &str)>,
+    ) -> Option<String> {
+        if max_words == 0 {
+            return None;
+        }
+
+        let (value, words) = self.stored_value_words(doc_id, attr)?;
+
+        if words.is_empty() {
+            return Some(value.trim().to_owned());
+        }
+
+        let window = best_window(&words, max_words);
+        let mut out = String::new();
+
+        if window.start > 0 {
+            out.push_str(ELLIPSIS);
+        }
+
+        let window_words = &words[window.clone()];
+
+        // the last word is a part of an identifier when it ends before the full one.
+        if let (Some(first), Some(end)) = (
+            window_words.first(),
+            window_words.iter().map(|w| w.1.end).max(),
+        ) {
+            // the punctuation before the first word or after the last one is kept
+            // when the value is not cropped on that side.
+            let start = match window.start {
+                0 => min(first.1.start, value.len() - value.trim_start().len()),
+                _ => first.1.start,
+            };
+
+            let end = match window.end == words.len() {
+                true => end.max(value.trim_end().len()),
+                false => end,
+            };
+
+            push_highlighted(&mut out, value, start..end, window_words, tags);
+        }
+
+        if window.end < words.len() {
+            out.push_str(ELLIPSIS);
+        }
+
+        Some(out)
+    }
+
+    /// Gets all the docs found from the most to the least relevant, sorting them
+    /// by their rank key computed once per doc, docs of the same relevance are
+    /// ordered by id.
+    pub fn sorted_doc_ids(&self) -> Vec<DocId> {
+        let mut keys = self
+            .iter()
+            .map(|id| (RankKey::new(id, self), id))
+            .collect::<Vec<_>>();
+
+        keys.sort_unstable();
+        keys.into_iter().map(|(_, id)| id).collect()
+    }
+
+    /// Iterates the docs found from the most to the least relevant, the docs are
+    /// sorted by pages as the iteration goes.
+    pub fn sorted_iter(&self) -> SortedDocIds<'a, '_> {
+        SortedDocIds::new(self)
+    }
+
+    /// Gets a page of the docs found, sorted from the most to the least relevant.
+    ///
+    /// Only the docs up to `offset + limit` are sorted, docs of the same relevance
+    /// are ordered by id.
+    pub fn top(&self, offset: usize, limit: usize) -> Vec<DocId> {
+        ranking::top(self, offset, limit)
+    }
+
     /// Gets matched words with distance for a document / attribute
     pub fn get_doc_attr_words_with_distance_and_query_index<'b>(
         &'b self,
         doc_id: DocId,
         attr: &str,
     ) -> impl Iterator<Item = (&'a str, Distance, QueryIndex)> + 'b {
-        let (slice, index_query) = self.get_doc_attr_words_with_distance_impl(doc_id, attr);
+        let (words, index_query) = self.get_doc_attr_words_with_distance_impl(doc_id, attr);
 
-        slice.iter().filter_map(|w| {
-            let q = index_query.get(*w)?;
-            Some((unsafe { &**w }, q.distance.0, q.query_index))
+        words.filter_map(|w| {
+            let q = index_query.get(w)?;
+            Some((unsafe { &*w }, q.distance.0, q.query_index))
         })
     }
 
@@ -58,7 +227,7 @@ impl<'a> SearchResults<'a> {
         &'b self,
         doc_id: DocId,
     ) -> impl Iterator<Item = (Attr<'a>, Word<'a>, Distance, QueryIndex)> + 'b {
-        self.searcher.attrs().iter().flat_map(move |a| {
+        self.attrs.iter().flat_map(move |a| {
             self.get_doc_attr_words_with_distance_and_query_index(doc_id, a.0)
                 .map(|(word, distance, query_index)| (&**a.0, word, distance, query_index))
         })
@@ -68,9 +237,9 @@ impl<'a> SearchResults<'a> {
         &'b self,
         doc_id: DocId,
         attr: &str,
-    ) -> (&'a [*const str], &'b IndexToQuery<'a>) {
-        let Some(a) = self.searcher.attrs().get(attr) else {
-            return (&[], &self.forward.index_to_query);
+    ) -> (DocAttrWords<'a>, &'b IndexToQuery) {
+        let Some(a) = self.attrs.get(attr) else {
+            return (DocAttrWords::empty(), &self.forward.index_to_query);
         };
 
         let results = match a.direction {
@@ -83,9 +252,131 @@ impl<'a> SearchResults<'a> {
             &results.index_to_query,
         )
     }
+
+    fn stored_value(&self, doc_id: DocId, attr: &str) -> Option<&'a str> {
+        let position = self.attrs.get_index_of(attr)?;
+        self.stored?.get(doc_id, position)
+    }
+
+    /// Gets the stored value of a doc attribute with the byte range of each word
+    /// and the index of the query word it matches.
+    fn stored_value_words(&self, doc_id: DocId, attr: &str) -> Option<(&'a str, Vec<ValueWord>)> {
+        let value = self.stored_value(doc_id, attr)?;
+        let a = self.attrs.get(attr)?;
+        let (words, index_to_query) = self.get_doc_attr_words_with_distance_impl(doc_id, attr);
+        let ranges = self
+            .direction_index_results(a.direction)
+            .index
+            .get_doc_attribute_ranges(doc_id, a.index);
+
+        let words = words
+            .zip(ranges)
+            .map(|(w, range)| (index_to_query.get(w).map(|e| e.query_index), range))
+            .collect();
+
+        Some((value, words))
+    }
 }
 
+/// Finds the window of at most `max_words` words matching the most distinct
+/// query words, then with the matched words the closest together, the first
+/// one on ties.
+///
+/// The words are counted by position, the parts of a token being in the
+/// window with it.
+fn best_window(words: &[ValueWord], max_words: usize) -> Range<usize> {
+    let mut starts = Vec::new();
+
+    for (i, position) in positions(words.iter().map(|w| w.1.clone())).enumerate() {
+        if position == starts.len() {
+            starts.push(i);
+        }
+    }
+
+    let word_range = |positions: Range<usize>| {
+        let start = starts.get(positions.start).copied().unwrap_or(words.len());
+        start..starts.get(positions.end).copied().unwrap_or(words.len())
+    };
+
+    let query_indexes = |position| {
+        words[word_range(position..position + 1)]
+            .iter()
+            .filter_map(|w| w.0)
+    };
+
+    let len = min(max_words, starts.len());
+    let query_len = words.iter().filter_map(|w| w.0).max().map_or(0, |q| q + 1);
+    let mut counts = vec![0usize; query_len];
+    let mut distinct = 0;
+    let mut best = ((0, Reverse(0)), 0..len);
+
+    let matched = (0..starts.len())
+        .filter(|p| query_indexes(*p).next().is_some())
+        .collect::<Vec<_>>();
+
+    for end in 0..starts.len() {
+        for q in query_indexes(end) {
+            counts[q] += 1;
+            distinct += usize::from(counts[q] == 1);
+        }
+
+        for q in end.checked_sub(len).into_iter().flat_map(query_indexes) {
+            counts[q] -= 1;
+            distinct -= usize::from(counts[q] == 0);
+        }
+
+        if end + 1 >= len {
+            let start = end + 1 - len;
+            let first = matched.partition_point(|p| *p < start);
+            let last = matched.partition_point(|p| *p <= end);
+
+            let span = match first < last {
+                true => matched[last - 1] - matched[first],
+                false => 0,
+            };
+
+            if (distinct, Reverse(span)) > best.0 {
+                best = ((distinct, Reverse(span)), start..end + 1);
+            }
+        }
+    }
+
+    word_range(best.1)
+}
+
+/// Pushes a span of the value, the matched words in it being wrapped between
+/// the tags if any.
+fn push_highlighted(
+    out: &mut String,
+    value: &str,
+    span: Range<usize>,
+    words: &[ValueWord],
+    tags: Option<(&str, &str)>,
+) {
+    let mut last = span.start;
+
+    if let Some((pre_tag, post_tag)) = tags {
+        for (_, range) in words.iter().filter(|w| w.0.is_some()) {
+            // the parts of an identifier are inside the range of the full one.
+            if range.start < last {
+                continue;
+            }
+
+            out.push_str(&value[last..range.start]);
+            out.push_str(pre_tag);
+            out.push_str(&value[range.clone()]);
+            out.push_str(post_tag);
+            last = range.end;
+        }
+    }
+
+    out.push_str(&value[last..span.end]);
+}
+
+const ELLIPSIS: &str = "…";
+
 type Attr<'a> = &'a str;
 type Distance = u8;
 type QueryIndex = usize;
+type ValueWord = (Option<QueryIndex>, Range<usize>);
 type Word<'a> = &'a str;
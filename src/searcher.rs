@@ -1,21 +1,51 @@
 use crate::{
-    AttrProps, Direction, DocId, Index, IndexLog, IndexResults, IndexToQuery, MatchEntry, Presence,
-    SearchQuery, SearchResults,
+    analyzer::tokenize,
+    binary::{
+        read_bool, read_direction, read_header, read_len, read_str, read_u32, read_u8, write_bool,
+        write_direction, write_header, write_len, write_str, write_u32, write_u8,
+    },
+    frozen,
+    fuzzy_policy::{read_fuzzy_policy, write_fuzzy_policy},
+    index::BulkEntry,
+    operation::check_operations,
+    stop_words::StopWords,
+    stored_values::StoredRef,
+    Analyzer, Analyzers, AttrProps, Direction, DocId, FuzzyPolicy, Index, IndexLog, IndexRef,
+    IndexResults, IndexToQuery, MatchEntry, Normalizer, Operation, OperationError, Presence,
+    SearchQuery, SearchResults, StoredValues, Tokenizer, Transaction, WordQuery, WordQueryOp,
 };
+use fxhash::{FxHashMap, FxHashSet};
 use indexmap::IndexMap;
 use once_cell::sync::OnceCell;
+#[cfg(feature = "rayon")]
+use rayon::prelude::*;
 use roaring::RoaringBitmap;
+use std::{
+    cmp::min,
+    collections::hash_map::Entry,
+    io::{self, Read, Write},
+    ops::Range,
+    slice,
+    sync::Arc,
+};
 
 pub type AttrMap = IndexMap<Box<str>, Attr, fxhash::FxBuildHasher>;
+pub(crate) type AttrsPriorities = OnceCell<Vec<Vec<PriorityDirectionIndexes>>>;
 type DirectionIndex = (Direction, usize);
-type PriorityDirectionIndexes = (u8, Vec<DirectionIndex>);
+pub(crate) type PriorityDirectionIndexes = (u8, Vec<DirectionIndex>);
+
+const SNAPSHOT_MAGIC: &[u8; 4] = b"SRCH";
+const SNAPSHOT_VERSION: u32 = 11;
 
 pub struct Searcher {
     attrs: AttrMap,
-    attrs_priorities: OnceCell<Vec<Vec<PriorityDirectionIndexes>>>,
+    attrs_priorities: AttrsPriorities,
     backward: Index,
     forward: Index,
     index_log: IndexLog,
+    stored: StoredValues,
+    analyzers: Analyzers,
+    stop_words: Arc<StopWords>,
 }
 
 impl Searcher {
@@ -26,47 +56,154 @@ impl Searcher {
             backward: Index::new(Direction::Backward),
             forward: Index::new(Direction::Forward),
             index_log: IndexLog::default(),
+            stored: StoredValues::default(),
+            analyzers: Analyzers::default(),
+            stop_words: Arc::default(),
+        }
+    }
+
+    /// Applies an operation, the operation is checked first and not applied
+    /// if invalid.
+    pub fn apply(&mut self, op: Operation) -> Result<(), OperationError> {
+        self.apply_all(slice::from_ref(&op))
+            .map_err(|mut errors| errors.swap_remove(0).1)
+    }
+
+    /// Applies operations in order if they are all valid, otherwise none is
+    /// applied and the errors are returned with the index of their operation.
+    pub fn apply_all(
+        &mut self,
+        operations: &[Operation],
+    ) -> Result<(), Vec<(usize, OperationError)>> {
+        let errors = check_operations(self, operations);
+
+        if !errors.is_empty() {
+            return Err(errors);
+        }
+
+        for op in operations {
+            self.apply_checked(op);
+        }
+
+        Ok(())
+    }
+
+    /// Applies an operation checked by [check_operations].
+    fn apply_checked(&mut self, op: &Operation) {
+        match op {
+            Operation::InsertDocAttribute {
+                doc_id,
+                name,
+                value,
+            } => self.insert_doc_attribute(*doc_id, name, value),
+            Operation::RemoveAttr { name } => {
+                self.remove_attr(name);
+            }
+            Operation::RemoveDoc { doc_id } => self.remove_doc(*doc_id),
+            Operation::SetAttribute { name, props } => {
+                self.set_attribute(name.to_string(), props.clone());
+            }
+            Operation::SetStopWords { culture, words } => {
+                self.set_stop_words(*culture, words.iter());
+            }
         }
     }
 
+    pub(crate) fn analyzers(&self) -> &Analyzers {
+        &self.analyzers
+    }
+
     pub(crate) fn attrs(&self) -> &AttrMap {
         &self.attrs
     }
 
     pub(crate) fn attrs_priorities(&self, culture: u8) -> &[PriorityDirectionIndexes] {
-        let by_cultures = self.attrs_priorities.get_or_init(|| {
-            let count = self
-                .attrs
-                .values()
-                .filter_map(|a| a.culture)
-                .max()
-                .unwrap_or_default()
-                + 1;
-            (0..count)
-                .map(|culture| self.compute_attr_priorities(culture))
-                .collect::<Vec<_>>()
-        });
-
-        by_cultures
-            .get(culture as usize)
-            .or_else(|| by_cultures.first())
-            .map_or(&[], |v| v)
+        culture_attrs_priorities(&self.attrs, &self.attrs_priorities, culture)
     }
 
-    fn compute_attr_priorities(&self, culture: u8) -> Vec<PriorityDirectionIndexes> {
-        let mut map = IndexMap::<u8, Vec<DirectionIndex>, fxhash::FxBuildHasher>::default();
+    /// Inserts many doc attributes, as calling [Searcher::insert_doc_attribute]
+    /// for each in order, without keeping the vocabulary and the word indexes
+    /// sorted after each new word. When a doc attribute is given several values,
+    /// only the last one is indexed.
+    ///
+    /// The values are tokenized, in parallel with the `rayon` feature, then the
+    /// new words are sorted once and merged in the indexes in a single pass.
+    pub fn bulk_insert<I, N, V>(&mut self, items: I)
+    where
+        I: IntoIterator<Item = (DocId, N, V)>,
+        N: AsRef<str>,
+        V: AsRef<str> + Sync,
+    {
+        let mut seen = FxHashSet::default();
+
+        // only the last value of a doc attribute is kept.
+        let mut items = items
+            .into_iter()
+            .filter_map(|(doc_id, name, value)| {
+                let position = self.attrs.get_index_of(name.as_ref())?;
+                Some((doc_id, position, value))
+            })
+            .collect::<Vec<_>>()
+            .into_iter()
+            .rev()
+            .filter(|(doc_id, position, _)| seen.insert((*doc_id, *position)))
+            .collect::<Vec<_>>();
+
+        items.reverse();
+
+        // the values of the attributes naming analyzers not registered are not indexed.
+        let (items, analyzers): (Vec<_>, Vec<_>) = items
+            .into_iter()
+            .filter_map(|item| {
+                let (_, a) = self.attrs.get_index(item.1).expect("attr");
+                Some((item, self.analyzers.attr(a)?))
+            })
+            .unzip();
+
+        let words = tokenize_values(&items, &analyzers);
+        let mut backward = Vec::new();
+        let mut forward = Vec::new();
+
+        for ((doc_id, position, value), words) in items.iter().zip(words) {
+            let (_, a) = self.attrs.get_index(*position).expect("attr");
+
+            if a.store {
+                self.stored.insert(*doc_id, *position, value.as_ref());
+            }
 
-        self.attrs
-            .values()
-            .filter(|a| a.culture.map_or(true, |c| c == culture))
-            .for_each(|a| {
-                map.entry(a.priority)
-                    .or_default()
-                    .push((a.direction, a.index))
+            let entries = match a.direction {
+                Direction::Backward => &mut backward,
+                Direction::Forward => &mut forward,
+            };
+
+            let (words, ranges): (Vec<_>, Vec<_>) = words.into_iter().unzip();
+
+            entries.push(BulkEntry {
+                attr_index: a.index,
+                doc_id: *doc_id,
+                ranges,
+                words,
             });
+        }
+
+        self.backward
+            .bulk_insert(backward, &mut self.index_log, &self.attrs);
+        self.forward
+            .bulk_insert(forward, &mut self.index_log, &self.attrs);
+    }
+
+    /// Gets the raw values of the stored attributes of a doc, with their attribute name.
+    pub fn get_doc(&self, doc_id: DocId) -> impl Iterator<Item = (&str, &str)> {
+        self.stored.get_doc(doc_id).filter_map(|(position, value)| {
+            let (name, _) = self.attrs.get_index(position)?;
+            Some((&**name, value))
+        })
+    }
 
-        map.sort_unstable_keys();
-        map.into_iter().collect()
+    /// Gets the raw value of a stored attribute of a doc, as it was inserted.
+    pub fn get_doc_attr_value(&self, doc_id: DocId, name: &str) -> Option<&str> {
+        let position = self.attrs.get_index_of(name)?;
+        self.stored.get(doc_id, position)
     }
 
     /// Gets the words inside a doc attribute as they are indexed.
@@ -93,105 +230,198 @@ impl Searcher {
         index.get_doc_attribute_words(doc_id, a.index)
     }
 
+    /// Checks if the value of an attribute gives words to index, the attribute
+    /// being known and its analyzer registered.
+    pub(crate) fn has_words(&self, name: &str, value: &str) -> bool {
+        let mut has_words = false;
+
+        if let Some(analyzer) = self.attrs.get(name).and_then(|a| self.analyzers.attr(a)) {
+            analyzer.words(value, &mut |_, _| has_words = true);
+        }
+
+        has_words
+    }
+
+    /// Inserts the value of a doc attribute, replacing the previous one.
+    ///
+    /// The value is not inserted when the attribute is unknown or names a
+    /// tokenizer or a normalizer not registered, see [Searcher::apply] to get
+    /// the error.
     pub fn insert_doc_attribute(&mut self, doc_id: DocId, name: &str, value: &str) {
-        if let Some(a) = self.attrs.get(name) {
+        let Some((position, _, a)) = self.attrs.get_full(name) else {
+            return;
+        };
+
+        if let Some(analyzer) = self.analyzers.attr(a) {
             direction_index_mut(a.direction, &mut self.backward, &mut self.forward)
-                .insert_doc_attribute(doc_id, a.index, value, &mut self.index_log, &self.attrs);
+                .insert_doc_attribute(
+                    doc_id,
+                    a.index,
+                    value,
+                    analyzer,
+                    &mut self.index_log,
+                    &self.attrs,
+                );
+
+            if a.store {
+                self.stored.insert(doc_id, position, value);
+            }
         }
     }
 
-    pub fn query<'a>(&'a self, query: &SearchQuery) -> SearchResults<'a> {
-        let mut backward_temp = Vec::new();
-        let mut forward_temp = Vec::new();
-        let mut backward_query = IndexToQuery::default();
-        let mut forward_query = IndexToQuery::default();
-        let mut required = None;
-        let mut denied = RoaringBitmap::new();
-        let mut optional = RoaringBitmap::new();
-
-        for q in &query.words {
-            self.forward.query(q, query.culture, &mut forward_temp);
-            self.backward.query(q, query.culture, &mut backward_temp);
-
-            match q.presence {
-                Presence::Optional => {
-                    add_entries(&mut optional, &forward_temp);
-                    add_entries(&mut optional, &backward_temp);
-                }
-                Presence::Denied => {
-                    add_entries(&mut denied, &forward_temp);
-                    add_entries(&mut denied, &backward_temp);
-                }
-                Presence::Required => {
-                    if forward_temp.is_empty() && backward_temp.is_empty() {
-                        required = Some(RoaringBitmap::new());
-                        break;
-                    }
+    /// Loads a searcher from a snapshot written by [Searcher::save_to].
+    ///
+    /// The tokenizers and normalizers are not saved, they must be registered
+    /// again before inserting docs or parsing queries, the values of the
+    /// attributes naming ones not registered are not inserted.
+    ///
+    /// The reader is read with many small reads, wrap it in a [std::io::BufReader]
+    /// when reading from a file.
+    pub fn load_from<R: Read>(mut reader: R) -> io::Result<Self> {
+        let r = &mut reader;
+
+        read_header(r, SNAPSHOT_MAGIC, SNAPSHOT_VERSION)?;
+
+        let attrs = read_attrs(r)?;
+        let stop_words = Arc::new(StopWords::read_from(r)?);
+        let backward = Index::read_from(Direction::Backward, &attrs, r)?;
+        let forward = Index::read_from(Direction::Forward, &attrs, r)?;
+
+        Ok(Self {
+            attrs,
+            attrs_priorities: OnceCell::new(),
+            backward,
+            forward,
+            index_log: IndexLog::default(),
+            stored: StoredValues::read_from(r)?,
+            analyzers: Analyzers::default(),
+            stop_words,
+        })
+    }
 
-                    if required.is_none() {
-                        required = Some(RoaringBitmap::full());
-                    }
+    /// Parses a query with the tokenizer and normalizer of its culture, so its
+    /// words are split and normalized as the values of the attributes without
+    /// their own.
+    pub fn parse_query(&self, culture: u8, s: &str) -> SearchQuery {
+        let analyzer = self.analyzers.culture(culture);
+        SearchQuery::parse_with(culture, s, analyzer.tokenizer, analyzer.normalizer)
+    }
 
-                    if let Some(r) = required.as_mut() {
-                        intersect_entries(r, &forward_temp);
-                        intersect_entries(r, &backward_temp);
-                    }
-                }
+    pub fn query<'a>(&'a self, query: &SearchQuery) -> SearchResults<'a> {
+        query_indexes(
+            &self.attrs,
+            self.attrs_priorities(query.culture),
+            IndexRef::Live(&self.backward),
+            IndexRef::Live(&self.forward),
+            Some(StoredRef::Live(&self.stored)),
+            &self.analyzers,
+            query,
+        )
+    }
+
+    /// Normalizes the stop words of each culture as the words of each group of
+    /// attributes, the groups naming a normalizer or tokenizer not registered
+    /// keeping their stop words.
+    fn normalize_stop_words(&mut self) {
+        let cultures = self
+            .attrs
+            .values()
+            .filter_map(|a| a.culture)
+            .max()
+            .map_or(1, |c| c as usize + 1);
+        let mut done = Vec::<DirectionIndex>::new();
+
+        for a in self.attrs.values() {
+            if done.contains(&(a.direction, a.group)) {
+                continue;
             }
 
-            backward_query.extend(q, backward_temp.drain(..));
-            forward_query.extend(q, forward_temp.drain(..));
-        }
+            done.push((a.direction, a.group));
 
-        let mut doc_ids = if optional.is_empty() {
-            required.unwrap_or_default()
-        } else if let Some(r) = required {
-            optional & r
-        } else {
-            optional
-        };
+            let index = direction_index_mut(a.direction, &mut self.backward, &mut self.forward);
 
-        doc_ids -= denied;
+            for culture in 0..cultures as u8 {
+                let Some(analyzer) = self.analyzers.attr_in(a, a.culture.and(Some(culture))) else {
+                    continue;
+                };
 
-        let backward_results = IndexResults {
-            index: &self.backward,
-            index_to_query: backward_query,
-        };
+                let mut normalized = FxHashSet::default();
 
-        let forward_results = IndexResults {
-            index: &self.forward,
-            index_to_query: forward_query,
-        };
+                for word in self
+                    .stop_words
+                    .culture(culture as usize)
+                    .into_iter()
+                    .flatten()
+                {
+                    let mut s = String::new();
+                    analyzer.normalizer.normalize(word, &mut s);
 
-        SearchResults::new(
-            backward_results,
-            query.culture,
-            doc_ids,
-            forward_results,
-            self,
-        )
+                    if !s.is_empty() {
+                        normalized.insert(s.into_boxed_str());
+                    }
+                }
+
+                index.set_stop_words(a.group, culture, normalized);
+            }
+        }
+    }
+
+    /// Registers a normalizer the attributes can refer to by name with
+    /// [AttrProps::normalizer], replacing the one with the same name.
+    ///
+    /// The values already inserted are not normalized again, the stop words
+    /// are.
+    pub fn register_normalizer(&mut self, name: &str, normalizer: Arc<dyn Normalizer>) {
+        self.analyzers.insert_normalizer(name, normalizer);
+        self.normalize_stop_words();
+    }
+
+    /// Registers a tokenizer the attributes can refer to by name with
+    /// [AttrProps::tokenizer], replacing the one with the same name.
+    ///
+    /// The values already inserted are not tokenized again.
+    pub fn register_tokenizer(&mut self, name: &str, tokenizer: Arc<dyn Tokenizer>) {
+        self.analyzers.insert_tokenizer(name, tokenizer);
     }
 
+    /// Numbers the attributes of a direction and their groups, the groups in
+    /// the order of their first attribute.
     fn reindex_attribute(&mut self, direction: Direction) {
-        self.attrs
+        let mut keys = Vec::<GroupKey>::new();
+
+        for (index, a) in self
+            .attrs
             .values_mut()
             .filter(|a| a.direction == direction)
             .enumerate()
-            .for_each(|(index, a)| a.index = index);
+        {
+            let key = a.group_key();
+
+            a.index = index;
+            a.group = match keys.iter().position(|k| *k == key) {
+                Some(group) => group,
+                None => {
+                    keys.push(key);
+                    keys.len() - 1
+                }
+            };
+        }
 
         self.attrs_priorities = OnceCell::new();
 
         direction_index_mut(direction, &mut self.backward, &mut self.forward)
-            .ensure_culture(&self.attrs, &mut self.index_log);
+            .ensure_groups(&self.attrs);
     }
 
     pub fn remove_attr(&mut self, name: &str) -> bool {
         let mut log = IndexLog::default();
 
-        match self.attrs.shift_remove(name) {
-            Some(a) => {
+        match self.attrs.shift_remove_full(name) {
+            Some((position, _, a)) => {
+                self.stored.remove_attr(position);
+
                 direction_index_mut(a.direction, &mut self.backward, &mut self.forward)
-                    .swap_remove_attr(a.index, a.culture, &mut log);
+                    .swap_remove_attr(a.index, a.culture, a.group, &mut log);
 
                 self.reindex_attribute(a.direction);
                 true
@@ -203,6 +433,85 @@ impl Searcher {
     pub fn remove_doc(&mut self, doc_id: DocId) {
         self.backward.remove_doc(doc_id, &mut self.index_log);
         self.forward.remove_doc(doc_id, &mut self.index_log);
+        self.stored.remove_doc(doc_id);
+    }
+
+    /// Writes a versioned binary snapshot of the searcher, attributes, both
+    /// indexes and stored values included, that can be loaded back with
+    /// [Searcher::load_from].
+    ///
+    /// The writer receives many small writes, wrap it in a [std::io::BufWriter]
+    /// when writing to a file.
+    pub fn save_to<W: Write>(&self, mut writer: W) -> io::Result<()> {
+        let w = &mut writer;
+
+        write_header(w, SNAPSHOT_MAGIC, SNAPSHOT_VERSION)?;
+        write_attrs(w, &self.attrs)?;
+        self.stop_words.write_to(w)?;
+        self.backward.write_to(w)?;
+        self.forward.write_to(w)?;
+        self.stored.write_to(w)?;
+        w.flush()
+    }
+
+    /// Writes the searcher in the format read by [crate::FrozenSearcher], meant to
+    /// be memory mapped by read only replicas.
+    ///
+    /// The writer receives many small writes, wrap it in a [std::io::BufWriter]
+    /// when writing to a file.
+    pub fn save_frozen_to<W: Write>(&self, mut writer: W) -> io::Result<()> {
+        let w = &mut writer;
+
+        write_header(w, frozen::MAGIC, frozen::VERSION)?;
+        write_attrs(w, &self.attrs)?;
+        frozen::write_index(w, &self.backward)?;
+        frozen::write_index(w, &self.forward)?;
+        frozen::write_stored(w, &self.stored)?;
+        w.flush()
+    }
+
+    /// Sets the normalizer of a culture, used by the attributes of the culture
+    /// without their own normalizer and by [Searcher::parse_query].
+    ///
+    /// The values already inserted are not normalized again, the stop words
+    /// are.
+    pub fn set_culture_normalizer(&mut self, culture: u8, normalizer: Arc<dyn Normalizer>) {
+        self.analyzers.set_culture_normalizer(culture, normalizer);
+        self.normalize_stop_words();
+    }
+
+    /// Sets the tokenizer of a culture, used by the attributes of the culture
+    /// without their own tokenizer and by [Searcher::parse_query].
+    ///
+    /// The values already inserted are not tokenized again.
+    pub fn set_culture_tokenizer(&mut self, culture: u8, tokenizer: Arc<dyn Tokenizer>) {
+        self.analyzers.set_culture_tokenizer(culture, tokenizer);
+    }
+
+    /// Sets the stop words of a culture, replacing its previous ones. The words
+    /// are normalized as the words of each group of attributes, with the
+    /// normalizer of the attributes or else of the culture, and again when a
+    /// normalizer is registered or set.
+    ///
+    /// A stop word stays in the words of the doc attributes, for the snippets,
+    /// and its docs are kept apart from the word index of the culture, so a
+    /// fuzzy query does not walk it. A query ignores it unless it is quoted,
+    /// denied or the query has only stop words. The docs of the words no longer
+    /// stop words are moved back to the word index.
+    pub fn set_stop_words<I, S>(&mut self, culture: u8, words: I)
+    where
+        I: IntoIterator<Item = S>,
+        S: AsRef<str>,
+    {
+        let words = words.into_iter().map(|w| w.as_ref().into()).collect();
+
+        Arc::make_mut(&mut self.stop_words).set(culture, words);
+        self.normalize_stop_words();
+    }
+
+    /// Starts a transaction, staging operations to apply atomically.
+    pub fn transaction(&mut self) -> Transaction<'_> {
+        Transaction::new(self)
     }
 
     pub fn set_attribute(&mut self, name: String, attr: AttrProps) -> bool {
@@ -214,17 +523,41 @@ impl Searcher {
                 Attr {
                     culture: attr.culture,
                     direction: attr.direction,
+                    fuzzy_policy: attr.fuzzy_policy,
+                    group: 0,
                     priority: attr.priority,
                     index: 0,
+                    normalizer: attr.normalizer,
+                    store: attr.store,
+                    tokenizer: attr.tokenizer,
                 },
             );
 
             self.reindex_attribute(attr.direction);
+            self.normalize_stop_words();
             true
         }
     }
 }
 
+/// A clone shares the indexed docs, words and stored values with the original
+/// by chunks, a chunk being copied when it is modified, see
+/// [crate::SharedSearcher].
+impl Clone for Searcher {
+    fn clone(&self) -> Self {
+        Self {
+            attrs: self.attrs.clone(),
+            attrs_priorities: self.attrs_priorities.clone(),
+            backward: self.backward.clone(),
+            forward: self.forward.clone(),
+            index_log: IndexLog::default(),
+            stored: self.stored.clone(),
+            analyzers: self.analyzers.clone(),
+            stop_words: self.stop_words.clone(),
+        }
+    }
+}
+
 impl Default for Searcher {
     #[inline]
     fn default() -> Self {
@@ -232,19 +565,123 @@ impl Default for Searcher {
     }
 }
 
+#[derive(Clone)]
 pub(crate) struct Attr {
     pub(crate) direction: Direction,
     pub(crate) culture: Option<u8>,
+    pub(crate) fuzzy_policy: Option<FuzzyPolicy>,
+
+    /// the group of the word indexes of the attribute in its direction.
+    pub(crate) group: usize,
     pub(crate) priority: u8,
     pub(crate) index: usize,
+    pub(crate) normalizer: Option<Box<str>>,
+    pub(crate) store: bool,
+    pub(crate) tokenizer: Option<Box<str>>,
+}
+
+impl Attr {
+    pub(crate) fn group_key(&self) -> GroupKey {
+        GroupKey {
+            fuzzy_policy: self.fuzzy_policy,
+            has_culture: self.culture.is_some(),
+            normalizer: self.normalizer.clone(),
+            tokenizer: self.tokenizer.clone(),
+        }
+    }
+}
+
+/// What the attributes sharing word indexes have in common, the words of a
+/// group of attributes being split and matched alike.
+///
+/// The attributes of a culture without their own tokenizer or normalizer use
+/// the ones of their culture, the word index of a culture of their group
+/// only has the words of the attributes of the culture.
+#[derive(Clone, PartialEq)]
+pub(crate) struct GroupKey {
+    fuzzy_policy: Option<FuzzyPolicy>,
+    has_culture: bool,
+    normalizer: Option<Box<str>>,
+    tokenizer: Option<Box<str>>,
 }
 
 fn add_entries(denied: &mut RoaringBitmap, entries: &[MatchEntry]) {
     for entry in entries {
-        *denied |= entry.docs;
+        entry.docs.union_into(denied);
     }
 }
 
+/// Gets the attributes grouped by priority for a culture, computed once for all cultures.
+pub(crate) fn culture_attrs_priorities<'a>(
+    attrs: &AttrMap,
+    attrs_priorities: &'a AttrsPriorities,
+    culture: u8,
+) -> &'a [PriorityDirectionIndexes] {
+    let by_cultures = attrs_priorities.get_or_init(|| {
+        let count = attrs
+            .values()
+            .filter_map(|a| a.culture)
+            .max()
+            .unwrap_or_default()
+            + 1;
+        (0..count)
+            .map(|culture| compute_attr_priorities(attrs, culture))
+            .collect::<Vec<_>>()
+    });
+
+    by_cultures
+        .get(culture as usize)
+        .or_else(|| by_cultures.first())
+        .map_or(&[], |v| v)
+}
+
+fn compute_attr_priorities(attrs: &AttrMap, culture: u8) -> Vec<PriorityDirectionIndexes> {
+    let mut map = IndexMap::<u8, Vec<DirectionIndex>, fxhash::FxBuildHasher>::default();
+
+    attrs
+        .values()
+        .filter(|a| a.culture.map_or(true, |c| c == culture))
+        .for_each(|a| {
+            map.entry(a.priority)
+                .or_default()
+                .push((a.direction, a.index))
+        });
+
+    map.sort_unstable_keys();
+    map.into_iter().collect()
+}
+
+/// Tokenizes the values on the rayon thread pool.
+#[cfg(feature = "rayon")]
+fn tokenize_values<V: AsRef<str> + Sync>(
+    items: &[(DocId, usize, V)],
+    analyzers: &[Analyzer],
+) -> Vec<Vec<(String, Range<u32>)>> {
+    items
+        .par_iter()
+        .zip(analyzers)
+        .with_min_len(MIN_TOKENIZE_CHUNK)
+        .map(|((_, _, v), analyzer)| tokenize(*analyzer, v.as_ref()))
+        .collect()
+}
+
+/// Tokenizes the values in order, no thread being spawned without rayon.
+#[cfg(not(feature = "rayon"))]
+fn tokenize_values<V: AsRef<str> + Sync>(
+    items: &[(DocId, usize, V)],
+    analyzers: &[Analyzer],
+) -> Vec<Vec<(String, Range<u32>)>> {
+    items
+        .iter()
+        .zip(analyzers)
+        .map(|((_, _, v), analyzer)| tokenize(*analyzer, v.as_ref()))
+        .collect()
+}
+
+/// The minimal number of values tokenized by a thread.
+#[cfg(feature = "rayon")]
+const MIN_TOKENIZE_CHUNK: usize = 256;
+
 fn direction_index_mut<'a>(
     direction: Direction,
     backward: &'a mut Index,
@@ -258,13 +695,402 @@ fn direction_index_mut<'a>(
 
 fn intersect_entries(required: &mut RoaringBitmap, entries: &[MatchEntry]) {
     for entry in entries {
-        *required &= entry.docs;
+        entry.docs.intersect_into(required);
+    }
+}
+
+/// The words of the terms of a query split by an analyzer, with the groups of
+/// attributes of each direction whose values are split by it.
+struct QueryAnalysis {
+    backward: AnalysisGroups,
+    forward: AnalysisGroups,
+    terms: Vec<Vec<WordQuery>>,
+}
+
+impl QueryAnalysis {
+    fn groups(&mut self, direction: Direction) -> &mut AnalysisGroups {
+        match direction {
+            Direction::Backward => &mut self.backward,
+            Direction::Forward => &mut self.forward,
+        }
+    }
+}
+
+/// Groups of attributes of a direction with the fuzzy policy of their
+/// attributes.
+#[derive(Default)]
+struct AnalysisGroups {
+    policies: Vec<(usize, FuzzyPolicy)>,
+}
+
+/// Splits the terms of a query once by distinct analyzer of the groups of
+/// attributes, the groups without registered analyzer being queried with the
+/// words of the query.
+///
+/// The same word of a term split by several analyzers keeps one index, the
+/// words being numbered term by term.
+fn analyze_query(
+    attrs: &AttrMap,
+    analyzers: &Analyzers,
+    query: &SearchQuery,
+) -> Vec<QueryAnalysis> {
+    let cultures = attrs
+        .values()
+        .filter_map(|a| a.culture)
+        .max()
+        .map_or(1, |c| c as usize + 1);
+
+    // as in the word indexes, an unknown culture is the first one.
+    let culture = match (query.culture as usize) < cultures {
+        true => query.culture,
+        false => 0,
+    };
+
+    let mut analyses = Vec::<(Option<Analyzer>, QueryAnalysis)>::new();
+
+    for a in attrs.values() {
+        let analyzer = analyzers.attr_in(a, a.culture.and(Some(culture)));
+
+        let position = analyses
+            .iter()
+            .position(|(other, _)| match (other, &analyzer) {
+                (Some(other), Some(analyzer)) => other.is_same(analyzer),
+                (other, analyzer) => other.is_none() && analyzer.is_none(),
+            });
+
+        let (_, analysis) = match position {
+            Some(position) => &mut analyses[position],
+            None => {
+                let terms = match analyzer {
+                    Some(analyzer) => query.term_words(analyzer),
+                    None => {
+                        let mut terms = vec![Vec::new(); query.term_count()];
+                        query
+                            .words
+                            .iter()
+                            .for_each(|w| terms[w.term].push(w.clone()));
+                        terms
+                    }
+                };
+
+                analyses.push((
+                    analyzer,
+                    QueryAnalysis {
+                        backward: AnalysisGroups::default(),
+                        forward: AnalysisGroups::default(),
+                        terms,
+                    },
+                ));
+                analyses.last_mut().expect("analysis")
+            }
+        };
+
+        let groups = analysis.groups(a.direction);
+
+        if !groups.policies.iter().any(|(group, _)| *group == a.group) {
+            groups
+                .policies
+                .push((a.group, a.fuzzy_policy.unwrap_or(query.fuzzy_policy)));
+        }
+    }
+
+    let mut indexes = FxHashMap::<(usize, Box<str>, WordQueryOp), usize>::default();
+
+    for term in 0..query.term_count() {
+        for (_, analysis) in &mut analyses {
+            for w in &mut analysis.terms[term] {
+                let len = indexes.len();
+                w.index = *indexes.entry((term, w.word.clone(), w.op)).or_insert(len);
+            }
+        }
+    }
+
+    analyses.into_iter().map(|(_, analysis)| analysis).collect()
+}
+
+/// Queries a word in the word indexes of groups of an index, a fuzzy word
+/// being matched with the policy of the attributes of each group, and a stop
+/// word in their stop words.
+fn query_index<'a>(
+    index: IndexRef<'a>,
+    groups: &[(usize, FuzzyPolicy)],
+    q: &WordQuery,
+    culture: u8,
+    stop_word: bool,
+    out: &mut Vec<MatchEntry<'a>>,
+) {
+    let query = |group: usize, policy: &FuzzyPolicy, out: &mut Vec<MatchEntry<'a>>| {
+        index.query(q, group, culture, policy, out);
+
+        if stop_word {
+            index.query_stop_word(q, group, culture, out);
+        }
+    };
+
+    if let [(group, policy)] = groups {
+        query(*group, policy, out);
+        return;
+    }
+
+    let mut matches = Vec::new();
+    let mut positions = FxHashMap::<*const str, usize>::default();
+
+    for (group, policy) in groups {
+        query(*group, policy, &mut matches);
+
+        for entry in matches.drain(..) {
+            // a word of many groups is kept once, with its best distance.
+            match positions.entry(entry.word) {
+                Entry::Occupied(o) => {
+                    let e = &mut out[*o.get()];
+                    e.distance = min(e.distance, entry.distance);
+                    e.docs.union_with(&entry.docs);
+                }
+                Entry::Vacant(v) => {
+                    v.insert(out.len());
+                    out.push(entry);
+                }
+            }
+        }
+    }
+}
+
+/// Runs a query on the backward and forward indexes, shared by the searchers.
+#[allow(clippy::too_many_arguments)]
+pub(crate) fn query_indexes<'a>(
+    attrs: &'a AttrMap,
+    attrs_priorities: &'a [PriorityDirectionIndexes],
+    backward: IndexRef<'a>,
+    forward: IndexRef<'a>,
+    stored: Option<StoredRef<'a>>,
+    analyzers: &'a Analyzers,
+    query: &SearchQuery,
+) -> SearchResults<'a> {
+    let mut backward_temp = Vec::new();
+    let mut forward_temp = Vec::new();
+    let mut backward_query = IndexToQuery::default();
+    let mut forward_query = IndexToQuery::default();
+    let mut required = None;
+    let mut denied = RoaringBitmap::new();
+    let mut optional = RoaringBitmap::new();
+
+    let analyses = analyze_query(attrs, analyzers, query);
+
+    // the groups of an analysis have the same normalizer, so the same stop words.
+    let is_stop_word = |analysis: &QueryAnalysis, q: &WordQuery| {
+        let (index, group) = match (
+            analysis.forward.policies.first(),
+            analysis.backward.policies.first(),
+        ) {
+            (Some((group, _)), _) => (forward, *group),
+            (None, Some((group, _))) => (backward, *group),
+            (None, None) => return false,
+        };
+
+        index.is_stop_word(group, query.culture, &q.word)
+    };
+
+    let only_stop_words = analyses
+        .iter()
+        .map(|analysis| {
+            analysis
+                .terms
+                .iter()
+                .flatten()
+                .all(|q| is_stop_word(analysis, q))
+        })
+        .collect::<Vec<_>>();
+
+    for term in 0..query.term_count() {
+        // the docs of a required term, having all its words split by an analyzer.
+        let mut term_required = None::<RoaringBitmap>;
+
+        for (analysis, only_stop_words) in analyses.iter().zip(&only_stop_words) {
+            let mut analysis_required = None::<RoaringBitmap>;
+
+            for q in &analysis.terms[term] {
+                let is_stop_word = is_stop_word(analysis, q);
+
+                // a stop word is only searched when quoted, denied or with other
+                // stop words.
+                if is_stop_word
+                    && q.op == WordQueryOp::Fuzzy
+                    && q.presence != Presence::Denied
+                    && !only_stop_words
+                {
+                    continue;
+                }
+
+                let stop_word =
+                    is_stop_word && matches!(q.op, WordQueryOp::Eq | WordQueryOp::Fuzzy);
+
+                query_index(
+                    forward,
+                    &analysis.forward.policies,
+                    q,
+                    query.culture,
+                    stop_word,
+                    &mut forward_temp,
+                );
+                query_index(
+                    backward,
+                    &analysis.backward.policies,
+                    q,
+                    query.culture,
+                    stop_word,
+                    &mut backward_temp,
+                );
+
+                match q.presence {
+                    Presence::Optional => {
+                        add_entries(&mut optional, &forward_temp);
+                        add_entries(&mut optional, &backward_temp);
+                    }
+                    Presence::Denied => {
+                        add_entries(&mut denied, &forward_temp);
+                        add_entries(&mut denied, &backward_temp);
+                    }
+                    Presence::Required => {
+                        let r = analysis_required.get_or_insert_with(RoaringBitmap::full);
+
+                        if forward_temp.is_empty() && backward_temp.is_empty() {
+                            r.clear();
+                        }
+
+                        intersect_entries(r, &forward_temp);
+                        intersect_entries(r, &backward_temp);
+                    }
+                }
+
+                backward_query.extend(q, backward_temp.drain(..));
+                forward_query.extend(q, forward_temp.drain(..));
+            }
+
+            if let Some(r) = analysis_required {
+                *term_required.get_or_insert_default() |= r;
+            }
+        }
+
+        if let Some(t) = term_required {
+            let r = required.get_or_insert_with(RoaringBitmap::full);
+            *r &= t;
+
+            if r.is_empty() {
+                break;
+            }
+        }
+    }
+
+    let mut doc_ids = if optional.is_empty() {
+        required.unwrap_or_default()
+    } else if let Some(r) = required {
+        optional & r
+    } else {
+        optional
+    };
+
+    doc_ids -= denied;
+
+    let backward_results = IndexResults {
+        index: backward,
+        index_to_query: backward_query,
+    };
+
+    let forward_results = IndexResults {
+        index: forward,
+        index_to_query: forward_query,
+    };
+
+    SearchResults::new(
+        attrs,
+        attrs_priorities,
+        backward_results,
+        doc_ids,
+        forward_results,
+        stored,
+    )
+}
+
+pub(crate) fn read_attrs<R: Read>(r: &mut R) -> io::Result<AttrMap> {
+    let mut attrs = AttrMap::default();
+
+    for _ in 0..read_len(r)? {
+        let name = read_str(r)?;
+        let direction = read_direction(r)?;
+        let culture = match read_bool(r)? {
+            true => Some(read_u8(r)?),
+            false => None,
+        };
+        let fuzzy_policy = read_fuzzy_policy(r)?;
+        let group = read_u32(r)? as usize;
+        let priority = read_u8(r)?;
+        let index = read_u32(r)? as usize;
+        let normalizer = match read_bool(r)? {
+            true => Some(read_str(r)?),
+            false => None,
+        };
+        let store = read_bool(r)?;
+        let tokenizer = match read_bool(r)? {
+            true => Some(read_str(r)?),
+            false => None,
+        };
+
+        attrs.insert(
+            name,
+            Attr {
+                culture,
+                direction,
+                fuzzy_policy,
+                group,
+                priority,
+                index,
+                normalizer,
+                store,
+                tokenizer,
+            },
+        );
+    }
+
+    Ok(attrs)
+}
+
+pub(crate) fn write_attrs<W: Write>(w: &mut W, attrs: &AttrMap) -> io::Result<()> {
+    write_len(w, attrs.len())?;
+
+    for (name, a) in attrs {
+        write_str(w, name)?;
+        write_direction(w, a.direction)?;
+        write_bool(w, a.culture.is_some())?;
+
+        if let Some(culture) = a.culture {
+            write_u8(w, culture)?;
+        }
+
+        write_fuzzy_policy(w, a.fuzzy_policy.as_ref())?;
+        write_u32(w, a.group as u32)?;
+        write_u8(w, a.priority)?;
+        write_u32(w, a.index as u32)?;
+        write_bool(w, a.normalizer.is_some())?;
+
+        if let Some(normalizer) = &a.normalizer {
+            write_str(w, normalizer)?;
+        }
+
+        write_bool(w, a.store)?;
+        write_bool(w, a.tokenizer.is_some())?;
+
+        if let Some(tokenizer) = &a.tokenizer {
+            write_str(w, tokenizer)?;
+        }
     }
+
+    Ok(())
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::{IdentifierTokenizer, MinMatchLevel, StandardNormalizer};
 
     #[test]
     fn insert_remove_backward() {
@@ -373,9 +1199,15 @@ mod tests {
         searcher.insert_doc_attribute(DocId::from(0), "*", "test");
         searcher.insert_doc_attribute(DocId::from(1), "*", "app");
 
+        // the attributes with and without culture are in different groups.
+        let groups = searcher.forward.groups();
+
         assert_eq!(2, searcher.forward.docs().len());
-        assert_eq!(4, searcher.forward.per_culture()[0].len());
-        assert_eq!(4, searcher.forward.per_culture()[1].len());
+        assert_eq!(2, groups.len());
+        assert_eq!(2, groups[0].per_culture()[0].len());
+        assert_eq!(2, groups[0].per_culture()[1].len());
+        assert_eq!(2, groups[1].per_culture()[0].len());
+        assert_eq!(2, groups[1].per_culture()[1].len());
         assert_eq!(
             searcher.forward.word_intern(),
             vec!["app", "balance", "encours", "test", "total"]
@@ -403,19 +1235,895 @@ mod tests {
     }
 
     #[test]
-    fn search_mandatory() {
+    fn add_culture_with_docs() {
         let mut searcher = Searcher::new();
+        searcher.set_attribute("en".into(), AttrProps::default().culture(0));
         searcher.set_attribute("*".into(), AttrProps::default());
 
-        searcher.insert_doc_attribute(DocId::from(0), "*", "balance echue");
-        searcher.insert_doc_attribute(DocId::from(1), "*", "balance courante");
-        searcher.insert_doc_attribute(DocId::from(2), "*", "valeur courante");
+        searcher.insert_doc_attribute(DocId::from(0), "en", "balance");
+        searcher.insert_doc_attribute(DocId::from(0), "*", "test app");
+        searcher.insert_doc_attribute(DocId::from(1), "*", "app");
 
-        let results = searcher.query(&SearchQuery::new(0, "+balance courante"));
+        searcher.set_attribute("de".into(), AttrProps::default().culture(2));
+
+        let groups = searcher.forward.groups();
+
+        assert_eq!(3, groups[0].per_culture().len());
+        assert_eq!(1, groups[0].per_culture()[0].len());
+        assert_eq!(0, groups[0].per_culture()[2].len());
+        assert_eq!(3, groups[1].per_culture().len());
+        assert_eq!(2, groups[1].per_culture()[1].len());
+        assert_eq!(2, groups[1].per_culture()[2].len());
+        assert_eq!(searcher.query(&SearchQuery::new(2, "app")).len(), 2);
+        assert_eq!(searcher.query(&SearchQuery::new(2, "balance")).len(), 0);
+    }
+
+    #[test]
+    fn fuzzy_policies() {
+        let mut searcher = Searcher::new();
+        searcher.set_attribute(
+            "code".into(),
+            AttrProps::default().fuzzy_policy(FuzzyPolicy::default().max_distance(0)),
+        );
+        searcher.set_attribute("desc".into(), AttrProps::default());
+
+        searcher.insert_doc_attribute(DocId::from(0), "code", "balance");
+        searcher.insert_doc_attribute(DocId::from(1), "desc", "balance");
+        searcher.insert_doc_attribute(DocId::from(2), "code", "balanse");
+
+        let doc_ids = |q: &str, policy: FuzzyPolicy| {
+            let mut query = SearchQuery::new(0, q);
+            query.set_fuzzy_policy(policy);
+
+            let results = searcher.query(&query);
+            (0..3)
+                .filter(|&id| results.contains_doc_id(DocId::from(id)))
+                .collect::<Vec<_>>()
+        };
+
+        let policy = FuzzyPolicy::default();
+
+        // the code attribute only matches without typo.
+        assert_eq!(doc_ids("balanse", policy), vec![1, 2]);
+        assert_eq!(doc_ids("balance", policy), vec![0, 1]);
+        assert_eq!(doc_ids("balanse", policy.max_distance(0)), vec![2]);
+
+        assert_eq!(doc_ids("balnace", policy.max_distance(1)), vec![1]);
+        assert_eq!(
+            doc_ids("balnace", policy.max_distance(1).transpositions(false)),
+            Vec::<u32>::new()
+        );
+
+        assert_eq!(doc_ids("valance", policy), vec![1]);
+        assert_eq!(
+            doc_ids("valance", policy.first_char_exact(true)),
+            Vec::<u32>::new()
+        );
+
+        assert_eq!(doc_ids("balanc", policy.min_lens(7, 8, 9)), vec![0, 1]);
+        assert_eq!(
+            doc_ids("balancx", policy.min_lens(8, 9, 10)),
+            Vec::<u32>::new()
+        );
+
+        // the policies of the attributes are kept by the snapshots.
+        let mut bytes = Vec::new();
+        searcher.save_to(&mut bytes).unwrap();
+
+        let loaded = Searcher::load_from(&bytes[..]).unwrap();
+        assert_eq!(loaded.query(&SearchQuery::new(0, "balanse")).len(), 2);
+
+        let mut bytes = Vec::new();
+        searcher.save_frozen_to(&mut bytes).unwrap();
+
+        let frozen = crate::FrozenSearcher::open(bytes).unwrap();
+        let results = frozen.query(&SearchQuery::new(0, "balanse"));
 
         assert!(!results.contains_doc_id(DocId::from(0)));
         assert!(results.contains_doc_id(DocId::from(1)));
-        assert!(!results.contains_doc_id(DocId::from(2)));
+    }
+
+    #[test]
+    fn fuzzy_policy_groups() {
+        let exact = AttrProps::default().fuzzy_policy(FuzzyPolicy::default().max_distance(0));
+
+        let mut searcher = Searcher::new();
+        searcher.set_attribute("code".into(), exact.clone());
+        searcher.set_attribute("desc".into(), AttrProps::default());
+        searcher.set_attribute("ref".into(), exact);
+
+        searcher.insert_doc_attribute(DocId::from(0), "code", "balanse");
+        searcher.insert_doc_attribute(DocId::from(0), "desc", "solde");
+        searcher.insert_doc_attribute(DocId::from(1), "desc", "balance");
+        searcher.insert_doc_attribute(DocId::from(2), "ref", "balanse balance");
+
+        let doc_ids = |searcher: &Searcher, q: &str| {
+            let results = searcher.query(&SearchQuery::new(0, q));
+            (0..3)
+                .filter(|&id| results.contains_doc_id(DocId::from(id)))
+                .collect::<Vec<_>>()
+        };
+
+        // the attributes with the same policy share their word indexes.
+        assert_eq!(2, searcher.forward.groups().len());
+        assert_eq!(doc_ids(&searcher, "balance"), vec![1, 2]);
+        assert_eq!(doc_ids(&searcher, "balanse"), vec![0, 1, 2]);
+
+        let mut bytes = Vec::new();
+        searcher.save_frozen_to(&mut bytes).unwrap();
+
+        let frozen = crate::FrozenSearcher::open(bytes).unwrap();
+        let results = frozen.query(&SearchQuery::new(0, "balance"));
+
+        assert!(!results.contains_doc_id(DocId::from(0)));
+        assert!(results.contains_doc_id(DocId::from(2)));
+
+        // a group without attributes is dropped, the other groups kept.
+        searcher.remove_attr("desc");
+        searcher.set_attribute("fr".into(), AttrProps::default().culture(1));
+        searcher.insert_doc_attribute(DocId::from(1), "fr", "solde");
+
+        assert_eq!(2, searcher.forward.groups().len());
+        assert_eq!(2, searcher.forward.groups()[0].per_culture().len());
+        assert_eq!(doc_ids(&searcher, "balance"), vec![2]);
+        assert_eq!(doc_ids(&searcher, "solde"), Vec::<u32>::new());
+
+        let mut bytes = Vec::new();
+        searcher.save_to(&mut bytes).unwrap();
+
+        let loaded = Searcher::load_from(&bytes[..]).unwrap();
+        assert_eq!(doc_ids(&loaded, "balanse"), vec![0, 2]);
+        assert!(loaded
+            .query(&SearchQuery::new(1, "solde"))
+            .contains_doc_id(DocId::from(1)));
+    }
+
+    #[test]
+    fn contains_and_ends_with() {
+        for direction in [Direction::Backward, Direction::Forward] {
+            let mut searcher = Searcher::new();
+            searcher.set_attribute("*".into(), AttrProps::default().direction(direction));
+
+            searcher.insert_doc_attribute(DocId::from(0), "*", "balance avalanche");
+            searcher.insert_doc_attribute(DocId::from(1), "*", "planche");
+            searcher.insert_doc_attribute(DocId::from(2), "*", "lancer cabale");
+            searcher.insert_doc_attribute(DocId::from(3), "*", "la");
+
+            let doc_ids = |searcher: &Searcher, q: &str| {
+                let results = searcher.query(&SearchQuery::new(0, q));
+                (0..4)
+                    .filter(|&id| results.contains_doc_id(DocId::from(id)))
+                    .collect::<Vec<_>>()
+            };
+
+            assert_eq!(doc_ids(&searcher, "*lan*"), vec![0, 1, 2], "{direction:?}");
+            assert_eq!(doc_ids(&searcher, "*nche"), vec![0, 1], "{direction:?}");
+            assert_eq!(doc_ids(&searcher, "*abal*"), vec![2], "{direction:?}");
+            assert_eq!(
+                doc_ids(&searcher, "*la*"),
+                vec![0, 1, 2, 3],
+                "{direction:?}"
+            );
+
+            searcher.remove_doc(DocId::from(1));
+
+            assert_eq!(doc_ids(&searcher, "*nche"), vec![0], "{direction:?}");
+        }
+    }
+
+    #[test]
+    fn save_load() {
+        let mut searcher = Searcher::new();
+        searcher.set_attribute("en".into(), AttrProps::default().culture(0));
+        searcher.set_attribute("fr".into(), AttrProps::default().culture(1));
+        searcher.set_attribute(
+            "*".into(),
+            AttrProps::default().direction(Direction::Backward),
+        );
+
+        searcher.insert_doc_attribute(DocId::from(0), "en", "balance");
+        searcher.insert_doc_attribute(DocId::from(1), "fr", "encours total");
+        searcher.insert_doc_attribute(DocId::from(2), "*", "air balle");
+
+        let mut buf = Vec::new();
+        searcher.save_to(&mut buf).unwrap();
+
+        let loaded = Searcher::load_from(&buf[..]).unwrap();
+
+        assert_eq!(3, loaded.attrs.len());
+        assert_eq!(2, loaded.forward.docs().len());
+        assert_eq!(2, loaded.forward.per_culture().len());
+        assert_eq!(
+            loaded.forward.word_intern(),
+            vec!["balance", "encours", "total"]
+        );
+        assert_eq!(loaded.backward.word_intern(), vec!["ellab", "ria"]);
+        assert_eq!(
+            loaded
+                .get_doc_attr_words(DocId::from(1), "fr")
+                .collect::<Vec<_>>(),
+            vec!["encours", "total"]
+        );
+
+        assert!(loaded
+            .query(&SearchQuery::new(1, "encour"))
+            .contains_doc_id(DocId::from(1)));
+
+        assert!(loaded
+            .query(&SearchQuery::new(0, "*alle"))
+            .contains_doc_id(DocId::from(2)));
+
+        assert!(!loaded
+            .query(&SearchQuery::new(1, "balance"))
+            .contains_doc_id(DocId::from(0)));
+    }
+
+    #[test]
+    fn stored_values() {
+        let mut searcher = Searcher::new();
+        searcher.set_attribute("title".into(), AttrProps::default().store(true));
+        searcher.set_attribute("body".into(), AttrProps::default());
+        searcher.set_attribute("code".into(), AttrProps::default().store(true));
+
+        searcher.insert_doc_attribute(DocId::from(0), "title", "Encours Total!");
+        searcher.insert_doc_attribute(DocId::from(0), "body", "balance");
+        searcher.insert_doc_attribute(DocId::from(0), "code", "B-42");
+        searcher.insert_doc_attribute(DocId::from(1), "code", "C-7");
+
+        assert_eq!(
+            searcher.get_doc_attr_value(DocId::from(0), "title"),
+            Some("Encours Total!")
+        );
+        assert_eq!(searcher.get_doc_attr_value(DocId::from(0), "body"), None);
+        assert_eq!(
+            searcher.get_doc(DocId::from(0)).collect::<Vec<_>>(),
+            vec![("title", "Encours Total!"), ("code", "B-42")]
+        );
+
+        let mut buf = Vec::new();
+        searcher.save_to(&mut buf).unwrap();
+        let mut loaded = Searcher::load_from(&buf[..]).unwrap();
+
+        assert!(loaded.remove_attr("title"));
+        assert_eq!(
+            loaded.get_doc(DocId::from(0)).collect::<Vec<_>>(),
+            vec![("code", "B-42")]
+        );
+
+        loaded.remove_doc(DocId::from(0));
+        assert_eq!(loaded.get_doc(DocId::from(0)).count(), 0);
+        assert_eq!(
+            loaded.get_doc_attr_value(DocId::from(1), "code"),
+            Some("C-7")
+        );
+    }
+
+    #[test]
+    fn load_invalid_snapshot() {
+        let mut buf = Vec::new();
+        Searcher::new().save_to(&mut buf).unwrap();
+
+        buf[4] = 0xff;
+        assert!(Searcher::load_from(&buf[..]).is_err());
+
+        assert!(Searcher::load_from(&b"nope"[..]).is_err());
+    }
+
+    #[test]
+    fn search_mandatory() {
+        let mut searcher = Searcher::new();
+        searcher.set_attribute("*".into(), AttrProps::default());
+
+        searcher.insert_doc_attribute(DocId::from(0), "*", "balance echue");
+        searcher.insert_doc_attribute(DocId::from(1), "*", "balance courante");
+        searcher.insert_doc_attribute(DocId::from(2), "*", "valeur courante");
+
+        let results = searcher.query(&SearchQuery::new(0, "+balance courante"));
+
+        assert!(!results.contains_doc_id(DocId::from(0)));
+        assert!(results.contains_doc_id(DocId::from(1)));
+        assert!(!results.contains_doc_id(DocId::from(2)));
+    }
+
+    #[test]
+    fn bulk_insert_as_sequential_inserts() {
+        fn new_searcher() -> Searcher {
+            let mut searcher = Searcher::new();
+            searcher.set_attribute("en".into(), AttrProps::default().culture(0));
+            searcher.set_attribute("fr".into(), AttrProps::default().culture(1).store(true));
+            searcher.set_attribute(
+                "*".into(),
+                AttrProps::default().direction(Direction::Backward),
+            );
+            searcher
+        }
+
+        let words = ["balance", "echue", "courante", "valeur", "encours", "total"];
+        let attrs = ["en", "fr", "*"];
+        let item = |i: u32, doc_id: u32, offset: usize| {
+            let i = i as usize + offset;
+            let value = format!("{} {} {i}", words[i % 6], words[i % 5]);
+            (DocId::from(doc_id), attrs[i % 3], value)
+        };
+
+        let mut first = (0..2000).map(|i| item(i, i % 700, 0)).collect::<Vec<_>>();
+
+        first.push((DocId::from(3), "unknown", "air".into()));
+        first.push((DocId::from(700), "fr", String::new()));
+
+        // values replacing the ones of the first insert, twice for some.
+        let second = (0..600).map(|i| item(i, i % 300, 3)).collect::<Vec<_>>();
+
+        let mut sequential = new_searcher();
+        let mut bulk = new_searcher();
+
+        for (doc_id, attr, value) in first.iter().chain(&second) {
+            sequential.insert_doc_attribute(*doc_id, attr, value);
+        }
+
+        bulk.bulk_insert(first.iter().map(|(d, a, v)| (*d, *a, v)));
+        bulk.bulk_insert(second.iter().map(|(d, a, v)| (*d, *a, v)));
+
+        let mut expected = Vec::new();
+        let mut actual = Vec::new();
+
+        sequential.save_to(&mut expected).unwrap();
+        bulk.save_to(&mut actual).unwrap();
+
+        assert!(expected == actual);
+        assert_eq!(
+            bulk.query(&SearchQuery::new(1, "courante")).len(),
+            sequential.query(&SearchQuery::new(1, "courante")).len()
+        );
+    }
+
+    #[test]
+    fn bulk_insert_mixed_directions() {
+        // the index of an attribute is by direction, not its position in the map.
+        let mut searcher = Searcher::new();
+        searcher.set_attribute(
+            "code".into(),
+            AttrProps::default()
+                .culture(1)
+                .direction(Direction::Backward),
+        );
+        searcher.set_attribute("en".into(), AttrProps::default().culture(0));
+        searcher.set_attribute("fr".into(), AttrProps::default().culture(1));
+
+        let mut sequential = searcher.clone();
+
+        let items = [
+            (DocId::from(0), "en", "house"),
+            (DocId::from(1), "fr", "maison"),
+            (DocId::from(2), "code", "ab12"),
+        ];
+
+        for (doc_id, attr, value) in items {
+            sequential.insert_doc_attribute(doc_id, attr, value);
+        }
+
+        searcher.bulk_insert(items);
+
+        for searcher in [&searcher, &sequential] {
+            let ids = |culture, text| {
+                let results = searcher.query(&SearchQuery::new(culture, text));
+                results.iter().collect::<Vec<_>>()
+            };
+
+            assert_eq!(ids(0, "house"), vec![DocId::from(0)]);
+            assert_eq!(ids(1, "house"), vec![]);
+            assert_eq!(ids(1, "maison"), vec![DocId::from(1)]);
+            assert_eq!(ids(0, "maison"), vec![]);
+            assert_eq!(ids(1, "*12"), vec![DocId::from(2)]);
+        }
+    }
+
+    #[test]
+    fn tokenizers() {
+        /// Splits on whitespace only.
+        struct Whitespace;
+
+        impl Tokenizer for Whitespace {
+            fn tokenize(&self, text: &str, sink: &mut dyn FnMut(&str, Range<usize>)) {
+                for word in text.split_whitespace() {
+                    let start = word.as_ptr() as usize - text.as_ptr() as usize;
+                    sink(word, start..start + word.len());
+                }
+            }
+        }
+
+        let mut searcher = Searcher::new();
+        searcher.set_attribute("en".into(), AttrProps::default().culture(0));
+        searcher.set_attribute("fr".into(), AttrProps::default().culture(1));
+        searcher.set_attribute(
+            "code".into(),
+            AttrProps::default().tokenizer("whitespace").store(true),
+        );
+        searcher.register_tokenizer("whitespace", Arc::new(Whitespace));
+        searcher.set_culture_tokenizer(1, Arc::new(Whitespace));
+
+        searcher.insert_doc_attribute(DocId::from(0), "code", "AB-12 x");
+        searcher.insert_doc_attribute(DocId::from(1), "en", "AB-12");
+        searcher.insert_doc_attribute(DocId::from(2), "fr", "AB-12");
+
+        let words = |doc_id, name| {
+            searcher
+                .get_doc_attr_words(DocId::from(doc_id), name)
+                .collect::<Vec<_>>()
+        };
+
+        assert_eq!(words(0, "code"), ["ab-12", "x"]);
+        assert_eq!(words(1, "en"), ["ab", "12"]);
+        assert_eq!(words(2, "fr"), ["ab-12"]);
+
+        let query = searcher.parse_query(1, "\"AB-12\"");
+        let results = searcher.query(&query);
+
+        assert!(results.contains_doc_id(DocId::from(0)));
+        assert!(!results.contains_doc_id(DocId::from(1)));
+        assert!(results.contains_doc_id(DocId::from(2)));
+        assert_eq!(
+            results.highlight(DocId::from(0), "code", "<", ">").unwrap(),
+            "<AB-12> x"
+        );
+
+        // the terms are split again with the tokenizer of each attribute.
+        let results = searcher.query(&searcher.parse_query(0, "ab-12"));
+
+        assert!(results.contains_doc_id(DocId::from(0)));
+        assert!(results.contains_doc_id(DocId::from(1)));
+        assert!(!results.contains_doc_id(DocId::from(2)));
+
+        let results = searcher.query(&SearchQuery::new(1, "\"ab-12\""));
+
+        assert!(!results.contains_doc_id(DocId::from(1)));
+        assert!(results.contains_doc_id(DocId::from(2)));
+
+        let mut data = Vec::new();
+        searcher.save_to(&mut data).unwrap();
+        let mut loaded = Searcher::load_from(&data[..]).unwrap();
+
+        assert_eq!(
+            loaded.attrs["code"].tokenizer.as_deref(),
+            Some("whitespace")
+        );
+
+        // the words of the values are highlighted by their ranges saved.
+        let results = loaded.query(&SearchQuery::new(0, "x"));
+        assert_eq!(
+            results.highlight(DocId::from(0), "code", "<", ">").unwrap(),
+            "AB-12 <x>"
+        );
+
+        // a value is not split with another tokenizer than the one named.
+        let op = Operation::InsertDocAttribute {
+            doc_id: DocId::from(3),
+            name: "code".into(),
+            value: "CD-34".into(),
+        };
+
+        assert_eq!(
+            loaded.apply(op.clone()),
+            Err(OperationError::UnknownAnalyzer("whitespace".into()))
+        );
+
+        loaded.insert_doc_attribute(DocId::from(3), "code", "CD-34");
+        assert_eq!(loaded.get_doc_attr_words(DocId::from(3), "code").count(), 0);
+
+        loaded.register_tokenizer("whitespace", Arc::new(Whitespace));
+        assert_eq!(loaded.apply(op), Ok(()));
+        assert_eq!(
+            loaded
+                .get_doc_attr_words(DocId::from(3), "code")
+                .collect::<Vec<_>>(),
+            ["cd-34"]
+        );
+    }
+
+    #[test]
+    fn normalizers() {
+        let mut searcher = Searcher::new();
+        searcher.set_attribute("es".into(), AttrProps::default().culture(0));
+        searcher.set_attribute("de".into(), AttrProps::default().culture(1));
+        searcher.set_attribute("code".into(), AttrProps::default().normalizer("wide"));
+        searcher.set_attribute("any".into(), AttrProps::default());
+        searcher.set_attribute("fr".into(), AttrProps::default().culture(2));
+        searcher.register_normalizer(
+            "wide",
+            Arc::new(StandardNormalizer::default().fold_full_width(true)),
+        );
+        searcher.set_culture_normalizer(0, Arc::new(StandardNormalizer::lowercase()));
+        searcher.set_culture_normalizer(1, Arc::new(StandardNormalizer::default().ligatures(true)));
+        searcher.set_culture_normalizer(2, Arc::new(StandardNormalizer::lowercase().nfkc(true)));
+
+        searcher.insert_doc_attribute(DocId::from(0), "es", "Año");
+        searcher.insert_doc_attribute(DocId::from(1), "es", "ano");
+        searcher.insert_doc_attribute(DocId::from(2), "de", "Straße");
+        searcher.insert_doc_attribute(DocId::from(3), "code", "ＡＢ１２");
+        searcher.insert_doc_attribute(DocId::from(4), "any", "Niño");
+        searcher.insert_doc_attribute(DocId::from(5), "fr", "Cafe\u{301} noir");
+
+        let doc_ids = |culture, s| {
+            let mut query = searcher.parse_query(culture, s);
+            query.set_min_match_level(MinMatchLevel::Equal);
+            searcher.query(&query).doc_ids().iter().collect::<Vec<_>>()
+        };
+
+        assert_eq!(doc_ids(0, "año"), [0]);
+        assert_eq!(doc_ids(0, "ano"), [1]);
+        assert_eq!(doc_ids(1, "strasse"), [2]);
+        assert_eq!(doc_ids(1, "STRASSE"), [2]);
+        assert_eq!(doc_ids(1, "ab12"), [3]);
+
+        // the attributes without culture are queried with their own normalizer.
+        assert_eq!(doc_ids(0, "niño"), [4]);
+
+        // the accent is composed by the normalizer, not split by the tokenizer.
+        assert_eq!(
+            searcher
+                .get_doc_attr_words(DocId::from(5), "fr")
+                .collect::<Vec<_>>(),
+            ["café", "noir"]
+        );
+        assert_eq!(doc_ids(2, "café"), [5]);
+        assert_eq!(doc_ids(2, "cafe\u{301}"), [5]);
+    }
+
+    #[test]
+    fn identifiers() {
+        let mut searcher = Searcher::new();
+        searcher.set_attribute(
+            "sku".into(),
+            AttrProps::default().tokenizer("identifier").store(true),
+        );
+        searcher.register_tokenizer("identifier", Arc::new(IdentifierTokenizer));
+
+        searcher.insert_doc_attribute(DocId::from(0), "sku", "ABC-123/X");
+        searcher.insert_doc_attribute(DocId::from(1), "sku", "snake_case_id");
+        searcher.insert_doc_attribute(DocId::from(2), "sku", "ABD-456");
+
+        let doc_ids = |s| {
+            let mut query =
+                SearchQuery::parse_with(0, s, &IdentifierTokenizer, &StandardNormalizer::default());
+            query.set_min_match_level(MinMatchLevel::Equal);
+            searcher.query(&query).doc_ids().iter().collect::<Vec<_>>()
+        };
+
+        assert_eq!(doc_ids("abc123"), [0]);
+        assert_eq!(doc_ids("123"), [0]);
+        assert_eq!(doc_ids("abc-123/x"), [0]);
+        assert_eq!(doc_ids("snakecaseid"), [1]);
+        assert_eq!(doc_ids("case"), [1]);
+
+        let results = searcher.query(&SearchQuery::new(0, "123"));
+
+        assert_eq!(
+            results.highlight(DocId::from(0), "sku", "<", ">").unwrap(),
+            "ABC-<123>/X"
+        );
+        assert_eq!(
+            results
+                .snippet_highlighted(DocId::from(0), "sku", 1, "<", ">")
+                .unwrap(),
+            "ABC-<123>/X"
+        );
+
+        let results = searcher.query(&SearchQuery::parse_with(
+            0,
+            "abc123x",
+            &IdentifierTokenizer,
+            &StandardNormalizer::default(),
+        ));
+
+        assert_eq!(
+            results.highlight(DocId::from(0), "sku", "<", ">").unwrap(),
+            "<ABC-123/X>"
+        );
+    }
+
+    #[test]
+    fn token_parts_positions() {
+        let mut searcher = Searcher::new();
+        searcher.set_attribute("*".into(), AttrProps::default().store(true));
+
+        searcher.insert_doc_attribute(DocId::from(0), "*", "john.doe@example.com at home in paris");
+        searcher.insert_doc_attribute(DocId::from(1), "*", "john is at home in paris");
+
+        // the parts of the email take its position, john is nearer to paris in
+        // the first doc.
+        let results = searcher.query(&SearchQuery::new(0, "john paris"));
+
+        assert_eq!(results.sorted_doc_ids(), [DocId::from(0), DocId::from(1)]);
+        assert_eq!(
+            results
+                .snippet_highlighted(DocId::from(0), "*", 2, "<", ">")
+                .unwrap(),
+            "<john.doe@example.com> at…"
+        );
+    }
+
+    #[test]
+    fn protected_tokens_query() {
+        let mut searcher = Searcher::new();
+        searcher.set_attribute("*".into(), AttrProps::default());
+
+        searcher.insert_doc_attribute(DocId::from(0), "*", "contact john.doe@example.com");
+        searcher.insert_doc_attribute(DocId::from(1), "*", "contact www.site.com");
+        searcher.insert_doc_attribute(DocId::from(2), "*", "release v1.23 and v2.0");
+
+        let doc_ids = |s| {
+            let query = SearchQuery::new(0, s);
+            searcher.query(&query).doc_ids().iter().collect::<Vec<_>>()
+        };
+
+        // only the whole token is denied, not its parts.
+        assert_eq!(doc_ids("contact -\"john.doe@example.com\""), [1]);
+        assert_eq!(doc_ids("v1.2*"), [2]);
+        assert_eq!(doc_ids("+v2.0"), [2]);
+    }
+
+    #[test]
+    fn stop_words() {
+        let mut searcher = Searcher::new();
+        searcher.set_attribute("fr".into(), AttrProps::default().culture(1).store(true));
+        searcher.set_attribute(
+            "*".into(),
+            AttrProps::default().direction(Direction::Backward),
+        );
+
+        searcher.insert_doc_attribute(DocId::from(0), "fr", "la ville de Paris");
+        searcher.set_stop_words(1, ["De", "la"]);
+        searcher.bulk_insert([
+            (DocId::from(1), "fr", "ville"),
+            (DocId::from(2), "*", "de la"),
+            (DocId::from(3), "fr", "maison"),
+        ]);
+
+        assert!(!searcher.forward.per_culture()[1].contains_word("de"));
+        assert!(!searcher.backward.per_culture()[1].contains_word("ed"));
+        assert!(searcher.backward.per_culture()[0].contains_word("ed"));
+        assert!(searcher.forward.groups()[0].stop_rows()[1].contains_word("de"));
+        assert!(searcher.backward.groups()[0].stop_rows()[1].contains_word("ed"));
+        assert!(!searcher.backward.groups()[0].stop_rows()[0].contains_word("ed"));
+        assert_eq!(
+            searcher
+                .get_doc_attr_words(DocId::from(0), "fr")
+                .collect::<Vec<_>>(),
+            ["la", "ville", "de", "paris"]
+        );
+
+        let doc_ids = |searcher: &Searcher, culture, s| {
+            let query = SearchQuery::new(culture, s);
+            searcher.query(&query).doc_ids().iter().collect::<Vec<_>>()
+        };
+
+        assert_eq!(doc_ids(&searcher, 1, "de ville"), [0, 1]);
+        assert_eq!(doc_ids(&searcher, 1, "de"), [0, 2]);
+        assert_eq!(doc_ids(&searcher, 1, "\"de\" ville"), [0, 1, 2]);
+        assert_eq!(doc_ids(&searcher, 0, "de"), [2]);
+        assert_eq!(doc_ids(&searcher, 1, "-de ville"), [1]);
+        assert_eq!(
+            searcher
+                .query(&SearchQuery::new(1, "de"))
+                .highlight(DocId::from(0), "fr", "<", ">")
+                .unwrap(),
+            "la ville <de> Paris"
+        );
+
+        let mut bytes = Vec::new();
+        searcher.save_to(&mut bytes).unwrap();
+
+        let loaded = Searcher::load_from(&bytes[..]).unwrap();
+
+        assert_eq!(doc_ids(&loaded, 1, "de ville"), [0, 1]);
+        assert_eq!(doc_ids(&loaded, 1, "la de"), [0, 2]);
+        assert_eq!(doc_ids(&loaded, 1, "-la ville"), [1]);
+
+        let mut bytes = Vec::new();
+        searcher.save_frozen_to(&mut bytes).unwrap();
+
+        let frozen = crate::FrozenSearcher::open(bytes).unwrap();
+
+        assert_eq!(
+            frozen
+                .query(&SearchQuery::new(1, "de"))
+                .doc_ids()
+                .iter()
+                .collect::<Vec<_>>(),
+            [0, 2]
+        );
+        assert_eq!(
+            frozen
+                .query(&SearchQuery::new(1, "-la ville"))
+                .doc_ids()
+                .iter()
+                .collect::<Vec<_>>(),
+            [1]
+        );
+
+        searcher.remove_doc(DocId::from(0));
+        searcher.insert_doc_attribute(DocId::from(4), "fr", "De");
+
+        assert_eq!(doc_ids(&searcher, 1, "de"), [2, 4]);
+
+        let mut t = searcher.transaction();
+        t.set_stop_words(1, &[]);
+        assert_eq!(t.commit(), Ok(()));
+
+        assert!(searcher.forward.per_culture()[1].contains_word("de"));
+        assert!(!searcher.forward.groups()[0].stop_rows()[1].contains_word("de"));
+        assert_eq!(doc_ids(&searcher, 1, "de ville"), [1, 2, 4]);
+    }
+
+    #[test]
+    fn stop_words_normalizers() {
+        let doc_ids = |searcher: &Searcher, culture, s| {
+            let query = searcher.parse_query(culture, s);
+            searcher.query(&query).doc_ids().iter().collect::<Vec<_>>()
+        };
+
+        // the stop words are normalized as the words of each group.
+        let mut searcher = Searcher::new();
+        searcher.register_normalizer("lowercase", Arc::new(StandardNormalizer::lowercase()));
+        searcher.set_attribute("name".into(), AttrProps::default().normalizer("lowercase"));
+        searcher.set_attribute(
+            "code".into(),
+            AttrProps::default().direction(Direction::Backward),
+        );
+        searcher.set_stop_words(0, ["À"]);
+        searcher.bulk_insert([
+            (DocId::from(0), "name", "Maison"),
+            (DocId::from(1), "name", "À Paris"),
+            (DocId::from(2), "code", "a b"),
+        ]);
+
+        assert!(searcher.forward.groups()[0].stop_rows()[0].contains_word("à"));
+        assert!(searcher.backward.groups()[0].stop_rows()[0].contains_word("a"));
+        assert_eq!(doc_ids(&searcher, 0, "à maison"), [0]);
+        assert_eq!(doc_ids(&searcher, 0, "a maison"), [0]);
+
+        // and again when the normalizer of their culture changes.
+        let mut searcher = Searcher::new();
+        searcher.set_attribute("title".into(), AttrProps::default().culture(1));
+        searcher.set_stop_words(1, ["À"]);
+        searcher.insert_doc_attribute(DocId::from(0), "title", "a b");
+
+        assert!(searcher.forward.groups()[0].stop_rows()[1].contains_word("a"));
+
+        searcher.set_culture_normalizer(1, Arc::new(StandardNormalizer::lowercase()));
+        searcher.insert_doc_attribute(DocId::from(1), "title", "À c");
+
+        assert!(searcher.forward.per_culture()[1].contains_word("a"));
+        assert!(searcher.forward.groups()[0].stop_rows()[1].contains_word("à"));
+        assert_eq!(doc_ids(&searcher, 1, "à b"), [0]);
+        assert_eq!(doc_ids(&searcher, 1, "a c"), [0, 1]);
+    }
+
+    #[test]
+    fn highlight() {
+        let mut searcher = Searcher::new();
+        searcher.set_attribute("title".into(), AttrProps::default().store(true));
+        searcher.set_attribute("body".into(), AttrProps::default());
+        searcher.set_attribute(
+            "code".into(),
+            AttrProps::default()
+                .direction(Direction::Backward)
+                .store(true),
+        );
+
+        searcher.insert_doc_attribute(DocId::from(0), "title", "Le Compte, Épargne: compté!");
+        searcher.insert_doc_attribute(DocId::from(0), "body", "compte");
+        searcher.insert_doc_attribute(DocId::from(0), "code", "AB-CDEpargne");
+
+        let results = searcher.query(&SearchQuery::new(0, "compte epar"));
+
+        assert_eq!(
+            results.highlight(DocId::from(0), "title", "<b>", "</b>"),
+            Some("Le <b>Compte</b>, <b>Épargne</b>: <b>compté</b>!".into())
+        );
+        assert_eq!(
+            results.highlight(DocId::from(0), "body", "<b>", "</b>"),
+            None
+        );
+
+        let results = searcher.query(&SearchQuery::new(0, "*pargne"));
+
+        assert_eq!(
+            results.highlight(DocId::from(0), "code", "[", "]"),
+            Some("AB-[CDEpargne]".into())
+        );
+    }
+
+    #[test]
+    fn snippet() {
+        let mut searcher = Searcher::new();
+        searcher.set_attribute("*".into(), AttrProps::default().store(true));
+
+        searcher.insert_doc_attribute(
+            DocId::from(0),
+            "*",
+            "Le solde du compte courant est positif. Le compte épargne est bloqué.",
+        );
+
+        let results = searcher.query(&SearchQuery::new(0, "compte epargne"));
+
+        assert_eq!(
+            results.snippet(DocId::from(0), "*", 4),
+            Some("…positif. Le compte épargne…".into())
+        );
+        assert_eq!(
+            results.snippet_highlighted(DocId::from(0), "*", 3, "<b>", "</b>"),
+            Some("…Le <b>compte</b> <b>épargne</b>…".into())
+        );
+        assert_eq!(
+            results.snippet(DocId::from(0), "*", 2),
+            Some("…compte épargne…".into())
+        );
+        assert_eq!(
+            results.snippet(DocId::from(0), "*", 100),
+            Some("Le solde du compte courant est positif. Le compte épargne est bloqué.".into())
+        );
+
+        searcher.insert_doc_attribute(
+            DocId::from(1),
+            "*",
+            "Compte du client, épargne bloquée. Le compte épargne est clos.",
+        );
+
+        let results = searcher.query(&SearchQuery::new(0, "compte epargne"));
+
+        assert_eq!(
+            results.snippet(DocId::from(1), "*", 4),
+            Some("…bloquée. Le compte épargne…".into())
+        );
+
+        let results = searcher.query(&SearchQuery::new(0, "clos"));
+
+        assert_eq!(
+            results.snippet(DocId::from(1), "*", 3),
+            Some("…épargne est clos.".into())
+        );
+        assert_eq!(results.snippet(DocId::from(1), "*", 0), None);
+
+        // a value without words is given whole, trimmed.
+        searcher.insert_doc_attribute(DocId::from(2), "*", " -- !! ");
+
+        let results = searcher.query(&SearchQuery::new(0, "clos"));
+
+        assert_eq!(
+            results.snippet(DocId::from(2), "*", 3),
+            Some("-- !!".into())
+        );
+        assert_eq!(
+            results.snippet_highlighted(DocId::from(2), "*", 3, "<", ">"),
+            Some("-- !!".into())
+        );
+    }
+
+    #[test]
+    fn search_results_doc_ids() {
+        let mut searcher = Searcher::new();
+        searcher.set_attribute("*".into(), AttrProps::default());
+
+        searcher.insert_doc_attribute(DocId::from(0), "*", "balance echue");
+        searcher.insert_doc_attribute(DocId::from(1), "*", "valeur courante");
+        searcher.insert_doc_attribute(DocId::from(5), "*", "balance courante");
+
+        let results = searcher.query(&SearchQuery::new(0, "balance"));
+
+        assert_eq!(2, results.len());
+        assert!(!results.is_empty());
+        assert_eq!(
+            results.iter().collect::<Vec<_>>(),
+            vec![DocId::from(0), DocId::from(5)]
+        );
+        assert_eq!(
+            results.doc_ids() & RoaringBitmap::from_iter([1, 5]),
+            RoaringBitmap::from_iter([5])
+        );
+        assert_eq!(results.into_doc_ids(), RoaringBitmap::from_iter([0, 5]));
+
+        assert!(searcher.query(&SearchQuery::new(0, "air")).is_empty());
     }
 
     #[test]
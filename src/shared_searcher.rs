@@ -0,0 +1,110 @@
+use crate::Searcher;
+use std::sync::{Arc, Mutex, PoisonError, RwLock};
+
+/// A searcher shared between threads, readers query an immutable version while
+/// a writer builds the next one, published atomically.
+///
+/// The next version is a clone of the current one sharing its docs, word rows,
+/// vocabulary and stored values by chunks, only the chunks touched by the
+/// writer are copied.
+pub struct SharedSearcher {
+    current: RwLock<Arc<Searcher>>,
+    writer: Mutex<()>,
+}
+
+impl SharedSearcher {
+    pub fn new(searcher: Searcher) -> Self {
+        Self {
+            current: RwLock::new(Arc::new(searcher)),
+            writer: Mutex::new(()),
+        }
+    }
+
+    /// Gets the current version, unaffected by the updates published after.
+    pub fn load(&self) -> Arc<Searcher> {
+        self.current
+            .read()
+            .unwrap_or_else(PoisonError::into_inner)
+            .clone()
+    }
+
+    /// Modifies a copy of the current version then publishes it, the updates
+    /// being run one at a time.
+    ///
+    /// Nothing is published if `f` panics.
+    pub fn update<F, R>(&self, f: F) -> R
+    where
+        F: FnOnce(&mut Searcher) -> R,
+    {
+        let _writer = self.writer.lock().unwrap_or_else(PoisonError::into_inner);
+        let mut next = Searcher::clone(&self.load());
+        let r = f(&mut next);
+
+        *self.current.write().unwrap_or_else(PoisonError::into_inner) = Arc::new(next);
+        r
+    }
+}
+
+impl Default for SharedSearcher {
+    #[inline]
+    fn default() -> Self {
+        Self::new(Searcher::new())
+    }
+}
+
+impl From<Searcher> for SharedSearcher {
+    #[inline]
+    fn from(searcher: Searcher) -> Self {
+        Self::new(searcher)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{AttrProps, DocId, SearchQuery};
+
+    #[test]
+    fn readers_keep_their_version() {
+        let shared = SharedSearcher::default();
+
+        shared.update(|s| {
+            s.set_attribute("*".into(), AttrProps::default().store(true));
+            s.insert_doc_attribute(DocId::from(0), "*", "balance echue");
+            s.insert_doc_attribute(DocId::from(1), "*", "balance courante");
+        });
+
+        let before = shared.load();
+
+        shared.update(|s| {
+            s.remove_doc(DocId::from(0));
+            s.insert_doc_attribute(DocId::from(1), "*", "valeur courante");
+        });
+
+        let after = shared.load();
+        let query = SearchQuery::new(0, "balance");
+
+        assert_eq!(before.query(&query).len(), 2);
+        assert_eq!(after.query(&query).len(), 0);
+        assert_eq!(
+            before.get_doc_attr_value(DocId::from(1), "*"),
+            Some("balance courante")
+        );
+        assert_eq!(
+            before
+                .get_doc_attr_words(DocId::from(0), "*")
+                .collect::<Vec<_>>(),
+            vec!["balance", "echue"]
+        );
+
+        drop(after);
+        let results = std::thread::scope(|scope| {
+            scope
+                .spawn(|| before.query(&SearchQuery::new(0, "courante")).len())
+                .join()
+                .unwrap()
+        });
+
+        assert_eq!(results, 1);
+    }
+}
@@ -0,0 +1,67 @@
+use crate::binary::{read_len, read_str, write_len, write_str};
+use fxhash::FxHashSet;
+use std::io::{self, Read, Write};
+
+/// The stop words of each culture, as given to the searcher or normalized as
+/// the words of a group of attributes.
+///
+/// A stop word is kept in the words of the doc attributes and its docs in the
+/// stop rows of the word groups rather than in the word index of its culture,
+/// it is ignored by the queries unless quoted, denied or only made of stop
+/// words.
+#[derive(Clone, Debug, Default)]
+pub(crate) struct StopWords(Vec<FxHashSet<Box<str>>>);
+
+impl StopWords {
+    pub(crate) fn contains(&self, culture: usize, word: &str) -> bool {
+        self.0
+            .get(culture)
+            .is_some_and(|words| words.contains(word))
+    }
+
+    pub(crate) fn is_empty(&self) -> bool {
+        self.0.iter().all(|words| words.is_empty())
+    }
+
+    pub(crate) fn culture(&self, culture: usize) -> Option<&FxHashSet<Box<str>>> {
+        self.0.get(culture)
+    }
+
+    /// Replaces the stop words of a culture, returns the previous ones.
+    pub(crate) fn set(&mut self, culture: u8, words: FxHashSet<Box<str>>) -> FxHashSet<Box<str>> {
+        let index = culture as usize;
+
+        if self.0.len() <= index {
+            self.0.resize_with(index + 1, Default::default);
+        }
+
+        std::mem::replace(&mut self.0[index], words)
+    }
+
+    pub(crate) fn truncate(&mut self, count: usize) {
+        self.0.truncate(count);
+    }
+
+    pub(crate) fn read_from<R: Read>(r: &mut R) -> io::Result<Self> {
+        (0..read_len(r)?)
+            .map(|_| (0..read_len(r)?).map(|_| read_str(r)).collect())
+            .collect::<io::Result<_>>()
+            .map(Self)
+    }
+
+    /// Writes the stop words of each culture, sorted to be written the same
+    /// way whatever the order of the set.
+    pub(crate) fn write_to<W: Write>(&self, w: &mut W) -> io::Result<()> {
+        write_len(w, self.0.len())?;
+
+        for words in &self.0 {
+            let mut words = words.iter().collect::<Vec<_>>();
+
+            words.sort_unstable();
+            write_len(w, words.len())?;
+            words.iter().try_for_each(|word| write_str(w, word))?;
+        }
+
+        Ok(())
+    }
+}
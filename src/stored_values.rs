@@ -0,0 +1,139 @@
+use crate::{
+    binary::{read_bool, read_len, read_str, write_bool, write_len, write_str},
+    frozen::FrozenStored,
+    ChunkedVec, DocId,
+};
+use std::{
+    io::{self, Read, Write},
+    sync::Arc,
+};
+
+/// The raw values of the stored attributes, by doc then by the position of the
+/// attribute in the attribute map.
+///
+/// The values of a doc are shared between clones by chunks of docs until
+/// modified.
+#[derive(Clone, Default)]
+pub(crate) struct StoredValues(ChunkedVec<Arc<Vec<Option<Box<str>>>>>);
+
+impl StoredValues {
+    /// Iterates the values of each doc by the position of their attribute.
+    pub(crate) fn docs(&self) -> impl Iterator<Item = &[Option<Box<str>>]> + Clone {
+        self.0.iter().map(|values| values.as_slice())
+    }
+
+    pub(crate) fn get(&self, doc_id: DocId, attr_position: usize) -> Option<&str> {
+        self.0.get(doc_id.index())?.get(attr_position)?.as_deref()
+    }
+
+    /// Iterates the values of a doc with the position of their attribute.
+    pub(crate) fn get_doc(&self, doc_id: DocId) -> impl Iterator<Item = (usize, &str)> {
+        self.0
+            .get(doc_id.index())
+            .into_iter()
+            .flat_map(|values| values.iter())
+            .enumerate()
+            .filter_map(|(position, v)| Some((position, v.as_deref()?)))
+    }
+
+    /// Stores the value of a doc attribute, an empty value removes it.
+    pub(crate) fn insert(&mut self, doc_id: DocId, attr_position: usize, value: &str) {
+        if value.is_empty() {
+            let is_stored = self
+                .0
+                .get(doc_id.index())
+                .and_then(|values| values.get(attr_position))
+                .is_some_and(Option::is_some);
+
+            if is_stored {
+                Arc::make_mut(self.0.get_mut(doc_id.index()).expect("doc"))[attr_position] = None;
+            }
+
+            return;
+        }
+
+        self.0.grow_with(doc_id.index() + 1, Default::default);
+
+        let values = Arc::make_mut(self.0.get_mut(doc_id.index()).expect("doc"));
+
+        if values.len() <= attr_position {
+            values.resize_with(attr_position + 1, || None);
+        }
+
+        values[attr_position] = Some(value.into());
+    }
+
+    pub(crate) fn read_from<R: Read>(r: &mut R) -> io::Result<Self> {
+        let mut docs = ChunkedVec::new();
+
+        for _ in 0..read_len(r)? {
+            let mut values = Vec::new();
+
+            for _ in 0..read_len(r)? {
+                values.push(match read_bool(r)? {
+                    true => Some(read_str(r)?),
+                    false => None,
+                });
+            }
+
+            docs.push(Arc::new(values));
+        }
+
+        Ok(Self(docs))
+    }
+
+    /// Removes the values of an attribute, the attributes after it moving back
+    /// by one position as in the attribute map.
+    pub(crate) fn remove_attr(&mut self, attr_position: usize) {
+        for doc_index in 0..self.0.len() {
+            if attr_position < self.0[doc_index].len() {
+                Arc::make_mut(self.0.get_mut(doc_index).expect("doc")).remove(attr_position);
+            }
+        }
+    }
+
+    pub(crate) fn remove_doc(&mut self, doc_id: DocId) {
+        if self
+            .0
+            .get(doc_id.index())
+            .is_some_and(|values| !values.is_empty())
+        {
+            *self.0.get_mut(doc_id.index()).expect("doc") = Default::default();
+        }
+    }
+
+    pub(crate) fn write_to<W: Write>(&self, w: &mut W) -> io::Result<()> {
+        write_len(w, self.0.len())?;
+
+        for values in &self.0 {
+            write_len(w, values.len())?;
+
+            for v in values.iter() {
+                write_bool(w, v.is_some())?;
+
+                if let Some(v) = v {
+                    write_str(w, v)?;
+                }
+            }
+        }
+
+        Ok(())
+    }
+}
+
+/// The stored values of a [crate::Searcher] or read in place by a
+/// [crate::FrozenSearcher].
+#[derive(Clone, Copy)]
+pub(crate) enum StoredRef<'a> {
+    Frozen(FrozenStored<'a>),
+    Live(&'a StoredValues),
+}
+
+impl<'a> StoredRef<'a> {
+    pub(crate) fn get(self, doc_id: DocId, attr_position: usize) -> Option<&'a str> {
+        match self {
+            Self::Frozen(stored) => stored.get(doc_id, attr_position),
+            Self::Live(stored) => stored.get(doc_id, attr_position),
+        }
+    }
+}
@@ -1,59 +1,162 @@
-#[derive(Debug)]
-pub(crate) struct StrIntern(Vec<Box<str>>);
+use crate::{
+    binary::{invalid_data, read_len, read_str, write_len, write_str},
+    chunked_vec::Iter,
+    trigram_index::TrigramIndex,
+    ChunkedVec,
+};
+use std::{
+    io::{self, Read, Write},
+    mem::take,
+    sync::Arc,
+};
+
+/// The strings are shared between the versions of a cloned intern by chunks,
+/// a string removed from a version staying alive while other versions refer
+/// to it.
+#[derive(Clone, Debug, Default)]
+pub(crate) struct StrIntern {
+    strs: ChunkedVec<Arc<str>>,
+    trigrams: TrigramIndex,
+}
 
 impl StrIntern {
-    pub(crate) const fn new() -> Self {
-        Self(Vec::new())
+    pub(crate) fn new() -> Self {
+        Self::default()
     }
 
     fn binary_search(&self, s: &str) -> Result<usize, usize> {
-        self.0.binary_search_by_key(&s, |r| r)
+        self.strs.binary_search_by_key(&s, |r| r)
+    }
+
+    /// Gets the sorted interned strings that may contain `s`, `None` when `s`
+    /// is too short for the trigram index.
+    pub(crate) fn containing_candidates(&self, s: &str) -> Option<Iter<'_, &'static str>> {
+        self.trigrams.candidates(s)
+    }
+
+    /// Gets an interned string by its position in the vocabulary.
+    pub(crate) fn get(&self, index: usize) -> Option<&'static str> {
+        self.strs.get(index).map(static_str)
+    }
+
+    /// Inserts sorted and deduplicated strings, merged with the vocabulary in
+    /// a single pass.
+    pub(crate) fn extend_sorted(&mut self, strs: &[&str]) {
+        let mut old = take(&mut self.strs).into_iter().peekable();
+        let mut vec = ChunkedVec::new();
+        let mut new = Vec::new();
+
+        for s in strs {
+            while let Some(o) = old.next_if(|o| **o < **s) {
+                vec.push(o);
+            }
+
+            if old.peek().is_none_or(|o| **o != **s) {
+                let s = Arc::from(*s);
+                new.push(static_str(&s));
+                vec.push(s);
+            }
+        }
+
+        vec.extend(old);
+        self.strs = vec;
+        self.trigrams.extend(&new);
+    }
+
+    /// Gets an interned string equal to `s`.
+    pub(crate) fn get_str(&self, s: &str) -> Option<&'static str> {
+        self.get(self.position(s)?)
     }
 
     pub(crate) fn insert(&mut self, s: &str) -> &'static str {
         let index = match self.binary_search(s) {
             Ok(index) => index,
             Err(index) => {
-                self.0.insert(index, s.into());
+                self.strs.insert(index, s.into());
+                self.trigrams.insert(static_str(&self.strs[index]));
                 index
             }
         };
 
-        unsafe {
-            // because it is a string interner, we assume the user
-            // will manage correctly the lifetime of the string.
-            let ptr: *const str = &**self.0.get_unchecked(index);
-            &*ptr
-        }
+        static_str(&self.strs[index])
+    }
+
+    /// Iterates the interned strings in order.
+    pub(crate) fn iter(&self) -> impl Clone + ExactSizeIterator<Item = &str> {
+        self.strs.iter().map(|s| &**s)
     }
 
     #[cfg(test)]
     pub(crate) fn len(&self) -> usize {
-        self.0.len()
+        self.strs.len()
+    }
+
+    /// Gets the position of an interned string in the vocabulary.
+    pub(crate) fn position(&self, s: &str) -> Option<usize> {
+        self.binary_search(s).ok()
+    }
+
+    pub(crate) fn read_from<R: Read>(r: &mut R) -> io::Result<Self> {
+        let len = read_len(r)?;
+        let mut vec = ChunkedVec::<Arc<str>>::new();
+
+        for _ in 0..len {
+            let s = read_str(r)?;
+
+            if vec.last().is_some_and(|last| **last >= *s) {
+                return Err(invalid_data("vocabulary is not sorted"));
+            }
+
+            vec.push(s.into());
+        }
+
+        // read rather than computed again from the words.
+        let trigrams = TrigramIndex::read_from(r, &vec)?;
+
+        Ok(Self {
+            strs: vec,
+            trigrams,
+        })
     }
 
     /// Remove and deallocate the string. Make sure that the string is not referenced before removed it.
     pub(crate) fn remove(&mut self, s: &str) {
         if let Ok(index) = self.binary_search(s) {
-            self.0.remove(index);
+            self.trigrams.remove(s);
+            self.strs.remove(index);
         }
     }
+
+    pub(crate) fn write_to<W: Write>(&self, w: &mut W) -> io::Result<()> {
+        write_len(w, self.strs.len())?;
+        self.strs.iter().try_for_each(|s| write_str(w, s))?;
+        self.trigrams.write_to(w, |s| self.position(s))
+    }
 }
 
-impl Default for StrIntern {
-    fn default() -> Self {
-        Self::new()
+pub(crate) fn static_str(s: &Arc<str>) -> &'static str {
+    unsafe {
+        // because it is a string interner, we assume the user
+        // will manage correctly the lifetime of the string.
+        let ptr: *const str = &**s;
+        &*ptr
     }
 }
 
 impl PartialEq<Vec<&str>> for StrIntern {
     fn eq(&self, other: &Vec<&str>) -> bool {
-        self.0.iter().map(|b| &**b).eq(other.iter().map(|s| &**s))
+        self.strs
+            .iter()
+            .map(|b| &**b)
+            .eq(other.iter().map(|s| &**s))
     }
 }
 
 impl PartialEq<Vec<&str>> for &StrIntern {
     fn eq(&self, other: &Vec<&str>) -> bool {
-        self.0.iter().map(|b| &**b).eq(other.iter().map(|s| &**s))
+        self.strs
+            .iter()
+            .map(|b| &**b)
+            .eq(other.iter().map(|s| &**s))
     }
 }
@@ -0,0 +1,169 @@
+/// A kind of token kept whole by the [DefaultTokenizer](crate::DefaultTokenizer),
+/// indexed as a single word and also split in its parts.
+#[derive(Clone, Copy, Debug, Eq, Hash, PartialEq)]
+pub enum TokenPattern {
+    /// An ISO date, as `2024-05-01`.
+    Date,
+
+    /// A decimal number, as `3.14`, `3,14` or `1,234.5`.
+    Decimal,
+
+    /// An email address, as `john.doe@example.com`.
+    Email,
+
+    /// An url starting with `http://`, `https://` or `www.`.
+    Url,
+
+    /// A version, as `v1.2`, `1.2.3` or `1.2.3-rc.1`.
+    Version,
+}
+
+impl TokenPattern {
+    pub(crate) const ALL: [Self; 5] = [
+        Self::Date,
+        Self::Decimal,
+        Self::Email,
+        Self::Url,
+        Self::Version,
+    ];
+
+    pub(crate) const fn bit(self) -> u8 {
+        1 << self as u8
+    }
+
+    /// Checks if a token, without the punctuation around it, is of this kind.
+    pub(crate) fn matches(self, token: &str) -> bool {
+        match self {
+            Self::Date => is_date(token),
+            Self::Decimal => is_decimal(token),
+            Self::Email => is_email(token),
+            Self::Url => is_url(token),
+            Self::Version => is_version(token),
+        }
+    }
+}
+
+fn is_date(s: &str) -> bool {
+    let b = s.as_bytes();
+
+    if b.len() != 10 || b[4] != b'-' || b[7] != b'-' {
+        return false;
+    }
+
+    let number = |r: &[u8]| {
+        r.iter().try_fold(0u16, |n, d| {
+            d.is_ascii_digit().then(|| n * 10 + (d - b'0') as u16)
+        })
+    };
+
+    number(&b[..4]).is_some()
+        && number(&b[5..7]).is_some_and(|m| (1..=12).contains(&m))
+        && number(&b[8..]).is_some_and(|d| (1..=31).contains(&d))
+}
+
+/// Checks a number with a single separator, the comma only after at most 3
+/// digits, or with thousands groups, so lists and ip addresses are not taken
+/// as decimals.
+fn is_decimal(s: &str) -> bool {
+    let Some((integer, fraction)) = s.rsplit_once(['.', ',']) else {
+        return false;
+    };
+
+    let separator = s.as_bytes()[integer.len()];
+
+    if !is_digit_groups([fraction].into_iter()) {
+        return false;
+    }
+
+    let Some((_, last_group)) = integer.rsplit_once(['.', ',']) else {
+        return is_digit_groups([integer].into_iter()) && (separator == b'.' || integer.len() <= 3);
+    };
+
+    let group_separator = integer.as_bytes()[integer.len() - last_group.len() - 1];
+    let mut groups = integer.split(group_separator as char);
+
+    groups.next().is_some_and(|g| (1..=3).contains(&g.len()))
+        && groups.all(|g| g.len() == 3)
+        && is_digit_groups(integer.split(group_separator as char))
+        && (separator != group_separator || fraction.len() == 3)
+}
+
+fn is_domain(s: &str) -> bool {
+    let label = |l: &str| !l.is_empty() && l.chars().all(|c| c.is_alphanumeric() || c == '-');
+
+    match s.rsplit_once('.') {
+        Some((name, tld)) => {
+            tld.len() >= 2 && tld.chars().all(char::is_alphabetic) && name.split('.').all(label)
+        }
+        None => false,
+    }
+}
+
+fn is_email(s: &str) -> bool {
+    let local_char = |c: char| c.is_alphanumeric() || matches!(c, '.' | '_' | '%' | '+' | '-');
+
+    match s.split_once('@') {
+        Some((local, domain)) => {
+            !local.is_empty() && local.chars().all(local_char) && is_domain(domain)
+        }
+        None => false,
+    }
+}
+
+fn is_url(s: &str) -> bool {
+    ["http://", "https://", "www."].iter().any(|prefix| {
+        s.len() > prefix.len()
+            && s.get(..prefix.len())
+                .is_some_and(|p| p.eq_ignore_ascii_case(prefix))
+    })
+}
+
+fn is_version(s: &str) -> bool {
+    let s = s.strip_prefix(['v', 'V']).unwrap_or(s);
+    let (number, pre_release) = s.split_once('-').unwrap_or((s, "a"));
+
+    number.contains('.')
+        && is_digit_groups(number.split('.'))
+        && !pre_release.is_empty()
+        && pre_release.chars().all(|c| c.is_alphanumeric() || c == '.')
+}
+
+fn is_digit_groups<'a>(mut groups: impl Iterator<Item = &'a str>) -> bool {
+    groups.all(|g| !g.is_empty() && g.bytes().all(|b| b.is_ascii_digit()))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn matches() {
+        let matching = |token| {
+            TokenPattern::ALL
+                .into_iter()
+                .filter(|p| p.matches(token))
+                .collect::<Vec<_>>()
+        };
+
+        assert_eq!(matching("2024-05-01"), [TokenPattern::Date]);
+        assert_eq!(matching("2024-13-01"), []);
+        assert_eq!(matching("3,14"), [TokenPattern::Decimal]);
+        assert_eq!(matching("1,234.5"), [TokenPattern::Decimal]);
+        assert_eq!(matching("1.234,5"), [TokenPattern::Decimal]);
+        assert_eq!(matching("1,234,567"), [TokenPattern::Decimal]);
+        assert_eq!(matching("1,2,3"), []);
+        assert_eq!(matching("2024,2025"), []);
+        assert_eq!(matching("192.168.1.1"), [TokenPattern::Version]);
+        assert_eq!(
+            matching("3.14"),
+            [TokenPattern::Decimal, TokenPattern::Version]
+        );
+        assert_eq!(matching("john.doe@example.com"), [TokenPattern::Email]);
+        assert_eq!(matching("john@localhost"), []);
+        assert_eq!(matching("HTTPS://example.com/a?b=1"), [TokenPattern::Url]);
+        assert_eq!(matching("www."), []);
+        assert_eq!(matching("v1.2.3-rc.1"), [TokenPattern::Version]);
+        assert_eq!(matching("v1"), []);
+        assert_eq!(matching("1."), []);
+    }
+}
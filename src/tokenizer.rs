@@ -0,0 +1,352 @@
+use crate::TokenPattern;
+use std::{iter::Peekable, ops::Range, str::CharIndices};
+use unicode_normalization::char::is_combining_mark;
+
+/// Splits a text into words, the values of the attributes when they are
+/// indexed and the queries when they are parsed, so both produce the same
+/// words. The words are then normalized by a [Normalizer](crate::Normalizer).
+///
+/// A word starting inside the range of the words before it takes their
+/// position, as the parts given after a whole token, so they do not move the
+/// next words away for the proximity and the snippets.
+pub trait Tokenizer: Send + Sync {
+    /// Calls `sink` with each word of `text` and its byte range in `text`, in
+    /// order.
+    fn tokenize(&self, text: &str, sink: &mut dyn FnMut(&str, Range<usize>));
+}
+
+/// Gets the position of each word from their byte ranges, in order.
+pub(crate) fn positions<I>(ranges: I) -> impl Iterator<Item = usize>
+where
+    I: IntoIterator<Item = Range<usize>>,
+{
+    ranges
+        .into_iter()
+        .scan((0, None::<usize>), |(position, end), range| {
+            match *end {
+                Some(e) if range.start < e => *end = Some(e.max(range.end)),
+                Some(_) => {
+                    *position += 1;
+                    *end = Some(range.end);
+                }
+                None => *end = Some(range.end),
+            }
+
+            Some(*position)
+        })
+}
+
+/// Splits the words on the transitions between letters and numbers, `#` and
+/// `°` being words by themselves.
+///
+/// The tokens of the protected [TokenPattern]s, all of them by default, are
+/// also given whole before their words, so `john.doe@example.com` gives
+/// `john.doe@example.com`, `john`, `doe`, `example` and `com`.
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub struct DefaultTokenizer {
+    protected: u8,
+}
+
+impl DefaultTokenizer {
+    /// A tokenizer without protected pattern, splitting all the tokens.
+    pub const fn unprotected() -> Self {
+        Self { protected: 0 }
+    }
+
+    /// Protects or not the tokens of a pattern.
+    pub const fn protect(mut self, pattern: TokenPattern, protect: bool) -> Self {
+        self.protected = match protect {
+            true => self.protected | pattern.bit(),
+            false => self.protected & !pattern.bit(),
+        };
+        self
+    }
+
+    fn is_protected(&self, token: &str) -> bool {
+        TokenPattern::ALL
+            .into_iter()
+            .any(|p| self.protected & p.bit() != 0 && p.matches(token))
+    }
+}
+
+impl Default for DefaultTokenizer {
+    fn default() -> Self {
+        DEFAULT_TOKENIZER
+    }
+}
+
+impl Tokenizer for DefaultTokenizer {
+    fn tokenize(&self, text: &str, sink: &mut dyn FnMut(&str, Range<usize>)) {
+        if self.protected == 0 {
+            return split_words(text, 0, sink);
+        }
+
+        for chunk in text.split_whitespace() {
+            let start = offset(text, chunk);
+            let token = chunk.trim_matches(|c| !is_word_char(c));
+
+            if self.is_protected(token) {
+                let token_start = offset(text, token);
+                sink(token, token_start..token_start + token.len());
+            }
+
+            split_words(chunk, start, sink);
+        }
+    }
+}
+
+pub(crate) static DEFAULT_TOKENIZER: DefaultTokenizer = DefaultTokenizer::unprotected()
+    .protect(TokenPattern::Date, true)
+    .protect(TokenPattern::Decimal, true)
+    .protect(TokenPattern::Email, true)
+    .protect(TokenPattern::Url, true)
+    .protect(TokenPattern::Version, true);
+
+/// Checks if a char belongs to a word, the combining marks staying with the
+/// char they follow so the normalizer can compose them.
+fn is_word_char(c: char) -> bool {
+    c.is_alphanumeric() || is_combining_mark(c)
+}
+
+/// Gets the byte offset of a slice of a text.
+fn offset(text: &str, slice: &str) -> usize {
+    slice.as_ptr() as usize - text.as_ptr() as usize
+}
+
+/// Calls `sink` with the words of a text starting at `start` in the text given
+/// to the tokenizer.
+fn split_words(text: &str, start: usize, sink: &mut dyn FnMut(&str, Range<usize>)) {
+    let mut chars = text.char_indices().peekable();
+
+    while let Some(range) = find_next_word(&mut chars) {
+        sink(&text[range.clone()], start + range.start..start + range.end);
+    }
+}
+
+/// Splits the text on whitespace into identifiers, as product codes or names
+/// of variables, giving for each one the full identifier, the identifier
+/// without its separators and its parts, split on the separators, the case
+/// changes and the transitions between letters and digits.
+///
+/// `ABC-123/X` gives `ABC-123/X`, `ABC123X`, `ABC`, `123` and `X`, so the doc
+/// is found by `abc-123/x`, `abc123` and `123`.
+#[derive(Clone, Copy, Debug, Default)]
+pub struct IdentifierTokenizer;
+
+impl Tokenizer for IdentifierTokenizer {
+    fn tokenize(&self, text: &str, sink: &mut dyn FnMut(&str, Range<usize>)) {
+        let mut compact = String::new();
+        let mut parts = Vec::new();
+
+        for token in text.split_whitespace() {
+            let token = token.trim_matches(|c| !is_word_char(c));
+            let start = offset(text, token);
+
+            parts.clear();
+            identifier_parts(token, &mut parts);
+
+            if parts.is_empty() {
+                continue;
+            }
+
+            sink(token, start..start + token.len());
+
+            if parts.len() == 1 {
+                continue;
+            }
+
+            compact.clear();
+            parts
+                .iter()
+                .for_each(|p| compact.push_str(&token[p.clone()]));
+
+            if compact.len() < token.len() {
+                sink(&compact, start..start + token.len());
+            }
+
+            for part in &parts {
+                sink(&token[part.clone()], start + part.start..start + part.end);
+            }
+        }
+    }
+}
+
+/// Gets the byte ranges of the parts of an identifier.
+fn identifier_parts(token: &str, parts: &mut Vec<Range<usize>>) {
+    let mut chars = token.char_indices().peekable();
+    let mut current = None::<(usize, char)>;
+
+    while let Some((i, c)) = chars.next() {
+        if current.is_some() && is_combining_mark(c) {
+            continue;
+        }
+
+        if !c.is_alphanumeric() {
+            if let Some((start, _)) = current.take() {
+                parts.push(start..i);
+            }
+
+            continue;
+        }
+
+        let start = match current {
+            Some((start, prev)) => {
+                let next_lower = chars.peek().is_some_and(|(_, n)| n.is_lowercase());
+
+                if prev.is_numeric() != c.is_numeric()
+                    || (prev.is_lowercase() && c.is_uppercase())
+                    || (prev.is_uppercase() && c.is_uppercase() && next_lower)
+                {
+                    parts.push(start..i);
+                    i
+                } else {
+                    start
+                }
+            }
+            None => i,
+        };
+
+        current = Some((start, c));
+    }
+
+    if let Some((start, _)) = current {
+        parts.push(start..token.len());
+    }
+}
+
+/// Reads the next word of a text, returns the byte range of the word in the text.
+fn find_next_word(chars: &mut Peekable<CharIndices>) -> Option<Range<usize>> {
+    #[derive(Clone, Copy)]
+    enum CharKind {
+        Whitespace,
+        Alpha,
+        Number,
+    }
+
+    let mut kind = CharKind::Whitespace;
+    let mut range = None::<Range<usize>>;
+
+    while let Some(&(i, c)) = chars.peek() {
+        if range.is_some() && is_combining_mark(c) {
+            // kept in the word, without changing its kind.
+        } else if c.is_alphabetic() {
+            if !matches!(kind, CharKind::Alpha | CharKind::Whitespace) {
+                break;
+            }
+
+            kind = CharKind::Alpha;
+        } else if c.is_numeric() {
+            if !matches!(kind, CharKind::Number | CharKind::Whitespace) {
+                break;
+            }
+
+            kind = CharKind::Number;
+        } else if c == '#' || c == '°' {
+            if matches!(kind, CharKind::Whitespace) {
+                chars.next();
+                range = Some(i..i + c.len_utf8());
+            }
+
+            break;
+        } else if range.is_some() {
+            chars.next();
+            break;
+        } else {
+            chars.next();
+            continue;
+        }
+
+        let start = range.map_or(i, |r| r.start);
+        range = Some(start..i + c.len_utf8());
+        chars.next();
+    }
+
+    range
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn tokens(tokenizer: &dyn Tokenizer, text: &str) -> Vec<String> {
+        let mut tokens = Vec::new();
+
+        tokenizer.tokenize(text, &mut |token, _| tokens.push(token.to_owned()));
+
+        tokens
+    }
+
+    #[test]
+    fn protected_patterns() {
+        let tokenizer = DefaultTokenizer::default();
+
+        assert_eq!(
+            tokens(&tokenizer, "Mail john.doe@example.com, le 2024-05-01."),
+            [
+                "Mail",
+                "john.doe@example.com",
+                "john",
+                "doe",
+                "example",
+                "com",
+                "le",
+                "2024-05-01",
+                "2024",
+                "05",
+                "01"
+            ]
+        );
+        assert_eq!(
+            tokens(&tokenizer, "(v1.2.3) 3,14"),
+            ["v1.2.3", "v", "1", "2", "3", "3,14", "3", "14"]
+        );
+        assert_eq!(
+            tokens(&tokenizer.protect(TokenPattern::Decimal, false), "3.14"),
+            ["3.14", "3", "14"]
+        );
+        assert_eq!(
+            tokens(&DefaultTokenizer::unprotected(), "3.14"),
+            ["3", "14"]
+        );
+        assert_eq!(
+            tokens(&tokenizer, "Cafe\u{301}, e\u{301}t\u{e9}"),
+            ["Cafe\u{301}", "e\u{301}t\u{e9}"]
+        );
+    }
+
+    #[test]
+    fn identifiers() {
+        let tokenizer = IdentifierTokenizer;
+        let mut ranges = Vec::new();
+
+        tokenizer.tokenize("ABC-123/X v2", &mut |_, range| ranges.push(range));
+        assert!(positions(ranges).eq([0, 0, 0, 0, 0, 1, 1, 1]));
+
+        assert_eq!(
+            tokens(&tokenizer, "(ABC-123/X),"),
+            ["ABC-123/X", "ABC123X", "ABC", "123", "X"]
+        );
+        assert_eq!(
+            tokens(&tokenizer, "camelCaseName snake_case_id"),
+            [
+                "camelCaseName",
+                "camel",
+                "Case",
+                "Name",
+                "snake_case_id",
+                "snakecaseid",
+                "snake",
+                "case",
+                "id"
+            ]
+        );
+        assert_eq!(
+            tokens(&tokenizer, "HTTPServer2 sku -"),
+            ["HTTPServer2", "HTTP", "Server", "2", "sku"]
+        );
+        assert_eq!(
+            tokens(&tokenizer, "Cafe\u{301}-2"),
+            ["Cafe\u{301}-2", "Cafe\u{301}2", "Cafe\u{301}", "2"]
+        );
+    }
+}
@@ -0,0 +1,123 @@
+use crate::{AttrProps, DocId, Operation, OperationError, Searcher};
+
+/// Operations staged on a [Searcher], applied all together by
+/// [Transaction::commit] or none of them.
+///
+/// Dropping a transaction without committing it rolls it back.
+pub struct Transaction<'a> {
+    operations: Vec<Operation>,
+    searcher: &'a mut Searcher,
+}
+
+impl<'a> Transaction<'a> {
+    pub(crate) fn new(searcher: &'a mut Searcher) -> Self {
+        Self {
+            operations: Vec::new(),
+            searcher,
+        }
+    }
+
+    /// Applies the staged operations in order if they are all valid, otherwise
+    /// none is applied and the errors are returned with the index of their
+    /// operation.
+    pub fn commit(self) -> Result<(), Vec<(usize, OperationError)>> {
+        self.searcher.apply_all(&self.operations)
+    }
+
+    pub fn insert_doc_attribute(&mut self, doc_id: DocId, name: &str, value: &str) -> &mut Self {
+        self.push(Operation::InsertDocAttribute {
+            doc_id,
+            name: name.into(),
+            value: value.into(),
+        })
+    }
+
+    /// The staged operations, in order.
+    pub fn operations(&self) -> &[Operation] {
+        &self.operations
+    }
+
+    pub fn push(&mut self, op: Operation) -> &mut Self {
+        self.operations.push(op);
+        self
+    }
+
+    pub fn remove_attr(&mut self, name: &str) -> &mut Self {
+        self.push(Operation::RemoveAttr { name: name.into() })
+    }
+
+    pub fn remove_doc(&mut self, doc_id: DocId) -> &mut Self {
+        self.push(Operation::RemoveDoc { doc_id })
+    }
+
+    /// Discards the staged operations.
+    pub fn rollback(self) {}
+
+    pub fn set_attribute(&mut self, name: &str, props: AttrProps) -> &mut Self {
+        self.push(Operation::SetAttribute {
+            name: name.into(),
+            props,
+        })
+    }
+
+    pub fn set_stop_words(&mut self, culture: u8, words: &[&str]) -> &mut Self {
+        self.push(Operation::SetStopWords {
+            culture,
+            words: words.iter().map(|w| (*w).into()).collect(),
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::SearchQuery;
+
+    #[test]
+    fn commit_rollback() {
+        let mut searcher = Searcher::new();
+        let mut t = searcher.transaction();
+
+        t.set_attribute("*", AttrProps::default())
+            .insert_doc_attribute(DocId::from(0), "*", "balance echue")
+            .insert_doc_attribute(DocId::from(1), "*", "balance courante");
+
+        assert_eq!(t.operations().len(), 3);
+        assert_eq!(t.commit(), Ok(()));
+
+        let mut t = searcher.transaction();
+        t.remove_doc(DocId::from(0));
+        t.rollback();
+
+        assert_eq!(searcher.query(&SearchQuery::new(0, "balance")).len(), 2);
+
+        let mut t = searcher.transaction();
+
+        t.remove_doc(DocId::from(0))
+            .remove_attr("*")
+            .insert_doc_attribute(DocId::from(1), "*", "valeur")
+            .set_attribute("*", AttrProps::default())
+            .set_attribute("*", AttrProps::default());
+
+        assert_eq!(
+            t.commit(),
+            Err(vec![
+                (2, OperationError::UnknownAttribute("*".into())),
+                (4, OperationError::AttributeExists("*".into())),
+            ])
+        );
+
+        assert_eq!(searcher.query(&SearchQuery::new(0, "balance")).len(), 2);
+        assert_eq!(
+            searcher.apply(Operation::RemoveAttr { name: "a".into() }),
+            Err(OperationError::UnknownAttribute("a".into()))
+        );
+        assert_eq!(
+            searcher.apply(Operation::RemoveDoc {
+                doc_id: DocId::from(0)
+            }),
+            Ok(())
+        );
+        assert_eq!(searcher.query(&SearchQuery::new(0, "balance")).len(), 1);
+    }
+}
@@ -0,0 +1,266 @@
+use crate::{
+    binary::{invalid_data, read_len, read_u32, write_len, write_u32},
+    chunked_vec::Iter,
+    str_intern::static_str,
+    ChunkedVec,
+};
+use fxhash::FxHashMap;
+use std::{
+    io::{self, Read, Write},
+    mem::take,
+    sync::Arc,
+};
+
+pub(crate) type Trigram = [u8; 3];
+
+/// Number of maps the trigrams are spread over.
+const SHARDS: usize = 64;
+
+type Shard = FxHashMap<Trigram, ChunkedVec<&'static str>>;
+
+static NO_WORDS: ChunkedVec<&'static str> = ChunkedVec::new();
+
+/// The words of a vocabulary by the trigrams of their bytes, to find the words
+/// containing a string without scanning the whole vocabulary.
+///
+/// The trigrams are spread over maps shared between the versions of a cloned
+/// index, a map being copied when one of its trigrams is modified. The sorted
+/// words of a trigram are chunked, inserting or removing a word moves and
+/// copies only the words of its chunk.
+#[derive(Clone, Debug, Default)]
+pub(crate) struct TrigramIndex(Vec<Arc<Shard>>);
+
+impl TrigramIndex {
+    /// Gets the sorted words sharing the rarest trigram of `s`, a superset of
+    /// the words containing `s`.
+    ///
+    /// Returns `None` when `s` is too short to have a trigram.
+    pub(crate) fn candidates(&self, s: &str) -> Option<Iter<'_, &'static str>> {
+        let mut trigrams = trigrams(s).peekable();
+        trigrams.peek()?;
+
+        let mut best = &NO_WORDS;
+
+        for (i, t) in trigrams.enumerate() {
+            let words = self.get(&t).unwrap_or(&NO_WORDS);
+
+            if i == 0 || words.len() < best.len() {
+                best = words;
+            }
+
+            if best.is_empty() {
+                break;
+            }
+        }
+
+        Some(best.iter())
+    }
+
+    /// Adds words not already in the index.
+    pub(crate) fn extend(&mut self, words: &[&'static str]) {
+        let mut new = words
+            .iter()
+            .flat_map(|w| trigrams(w).map(move |t| (t, *w)))
+            .collect::<Vec<_>>();
+
+        new.sort_unstable();
+        new.dedup();
+
+        for group in new.chunk_by(|a, b| a.0 == b.0) {
+            let words = self.shard_mut(&group[0].0).entry(group[0].0).or_default();
+
+            // merged in a single pass, the words of a trigram staying sorted.
+            let mut old = take(words).into_iter().peekable();
+
+            for (_, w) in group {
+                while let Some(o) = old.next_if(|o| o < w) {
+                    words.push(o);
+                }
+
+                words.push(w);
+            }
+
+            words.extend(old);
+        }
+    }
+
+    fn get(&self, t: &Trigram) -> Option<&ChunkedVec<&'static str>> {
+        self.0.get(shard(t))?.get(t)
+    }
+
+    pub(crate) fn insert(&mut self, word: &'static str) {
+        for t in unique_trigrams(word) {
+            let words = self.shard_mut(&t).entry(t).or_default();
+
+            if let Err(index) = words.binary_search_by_key(&word, |w| *w) {
+                words.insert(index, word);
+            }
+        }
+    }
+
+    /// Reads the words of the trigrams, written as their position in the
+    /// sorted vocabulary `words`.
+    pub(crate) fn read_from<R: Read>(r: &mut R, words: &ChunkedVec<Arc<str>>) -> io::Result<Self> {
+        let mut index = Self::default();
+
+        for _ in 0..read_len(r)? {
+            let [a, b, c, _] = read_u32(r)?.to_le_bytes();
+            let t = [a, b, c];
+            let mut trigram_words = ChunkedVec::new();
+            let mut last = None;
+
+            for _ in 0..read_len(r)? {
+                let id = read_u32(r)? as usize;
+
+                if last.is_some_and(|last| last >= id) {
+                    return Err(invalid_data("trigram words are not sorted"));
+                }
+
+                let word = words
+                    .get(id)
+                    .ok_or_else(|| invalid_data("word not found in vocabulary"))?;
+
+                trigram_words.push(static_str(word));
+                last = Some(id);
+            }
+
+            index.shard_mut(&t).insert(t, trigram_words);
+        }
+
+        Ok(index)
+    }
+
+    pub(crate) fn remove(&mut self, word: &str) {
+        for t in unique_trigrams(word) {
+            let Some(Ok(index)) = self
+                .get(&t)
+                .map(|words| words.binary_search_by_key(&word, |w| *w))
+            else {
+                continue;
+            };
+
+            let shard = self.shard_mut(&t);
+
+            if shard[&t].len() == 1 {
+                shard.remove(&t);
+            } else {
+                shard.get_mut(&t).expect("trigram").remove(index);
+            }
+        }
+    }
+
+    /// Gets the map of a trigram to modify, copying it if shared.
+    fn shard_mut(&mut self, t: &Trigram) -> &mut Shard {
+        if self.0.is_empty() {
+            self.0 = vec![Arc::default(); SHARDS];
+        }
+
+        Arc::make_mut(&mut self.0[shard(t)])
+    }
+
+    /// Writes the sorted trigrams with their words, as their position in the
+    /// vocabulary given by `position`.
+    pub(crate) fn write_to<W, F>(&self, w: &mut W, mut position: F) -> io::Result<()>
+    where
+        W: Write,
+        F: FnMut(&str) -> Option<usize>,
+    {
+        let mut trigrams = self
+            .0
+            .iter()
+            .flat_map(|shard| shard.iter())
+            .collect::<Vec<_>>();
+
+        trigrams.sort_unstable_by_key(|(t, _)| **t);
+        write_len(w, trigrams.len())?;
+
+        for ([a, b, c], words) in trigrams {
+            write_u32(w, u32::from_le_bytes([*a, *b, *c, 0]))?;
+            write_len(w, words.len())?;
+
+            for word in words {
+                let id =
+                    position(word).ok_or_else(|| invalid_data("word not found in vocabulary"))?;
+                write_u32(w, id as u32)?;
+            }
+        }
+
+        Ok(())
+    }
+}
+
+fn shard(t: &Trigram) -> usize {
+    fxhash::hash(t) % SHARDS
+}
+
+pub(crate) fn trigrams(s: &str) -> impl Iterator<Item = Trigram> + '_ {
+    s.as_bytes().windows(3).map(|w| [w[0], w[1], w[2]])
+}
+
+pub(crate) fn unique_trigrams(s: &str) -> Vec<Trigram> {
+    let mut trigrams = trigrams(s).collect::<Vec<_>>();
+    trigrams.sort_unstable();
+    trigrams.dedup();
+    trigrams
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn words(index: &TrigramIndex, s: &str) -> Option<Vec<&'static str>> {
+        index.candidates(s).map(|words| words.copied().collect())
+    }
+
+    #[test]
+    fn candidates() {
+        let mut index = TrigramIndex::default();
+
+        index.extend(&["balance", "balle", "cabale"]);
+        index.insert("aaaa");
+        index.insert("avalanche");
+
+        assert_eq!(words(&index, "al"), None);
+        assert_eq!(
+            words(&index, "bal"),
+            Some(vec!["balance", "balle", "cabale"])
+        );
+        assert_eq!(words(&index, "lanc"), Some(vec!["avalanche", "balance"]));
+        assert_eq!(words(&index, "aaa"), Some(vec!["aaaa"]));
+        assert_eq!(words(&index, "balx"), Some(vec![]));
+
+        let copy = index.clone();
+
+        index.remove("balance");
+        index.remove("aaaa");
+
+        assert_eq!(words(&index, "lanc"), Some(vec!["avalanche"]));
+        assert_eq!(words(&index, "aaa"), Some(vec![]));
+        assert_eq!(words(&copy, "lanc"), Some(vec!["avalanche", "balance"]));
+    }
+
+    #[test]
+    fn write_read() {
+        let vocabulary = ["avalanche", "balance", "balle", "cabale"]
+            .map(Arc::<str>::from)
+            .into_iter()
+            .collect::<ChunkedVec<_>>();
+
+        let mut index = TrigramIndex::default();
+        index.extend(&vocabulary.iter().map(static_str).collect::<Vec<_>>());
+
+        let position = |w: &str| vocabulary.binary_search_by_key(&w, |v| &**v).ok();
+        let mut buf = Vec::new();
+
+        index.write_to(&mut buf, position).unwrap();
+
+        let read = TrigramIndex::read_from(&mut &buf[..], &vocabulary).unwrap();
+
+        assert_eq!(
+            words(&read, "bal"),
+            Some(vec!["balance", "balle", "cabale"])
+        );
+        assert_eq!(words(&read, "lanc"), Some(vec!["avalanche", "balance"]));
+        assert_eq!(words(&read, "xyz"), Some(vec![]));
+    }
+}
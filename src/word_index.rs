@@ -1,64 +1,65 @@
-use crate::{match_entry::MatchEntry, DocId, MatchDistance, StrIntern};
-use levenshtein_automata::{Distance, DFA};
+use crate::{
+    binary::{invalid_data, read_bitmap, read_len, read_u32, write_bitmap, write_len, write_u32},
+    match_entry::{MatchDocs, MatchEntry},
+    ChunkedVec, DocId, MatchDistance, StrIntern,
+};
+use levenshtein_automata::{Distance, DFA, SINK_STATE};
 use roaring::RoaringBitmap;
-use std::cmp::min;
-
-pub(crate) struct WordIndex(Vec<WordIndexRow>);
+use std::{
+    borrow::Cow,
+    cmp::min,
+    io::{self, Read, Write},
+    mem::take,
+    sync::Arc,
+};
+
+/// Verifies a word of the index against the word of a query.
+pub(crate) type WordPredicate = fn(&str, &str) -> bool;
+
+/// The rows are shared between the versions of a cloned index by chunks, a
+/// chunk being copied when one of its rows is inserted or removed and a row
+/// when it is modified.
+#[derive(Clone)]
+pub(crate) struct WordIndex(ChunkedVec<Arc<WordIndexRow>>);
 
 impl WordIndex {
     pub(crate) const fn new() -> Self {
-        Self(Vec::new())
+        Self(ChunkedVec::new())
     }
 
     fn binary_search(&self, word: &str) -> Result<usize, usize> {
         self.0.binary_search_by_key(&word, |t| t.word)
     }
 
-    pub(crate) fn contains<'a>(&'a self, word: &str, out: &mut Vec<MatchEntry<'a>>) {
-        out.extend(
-            self.0
-                .iter()
-                .filter(|r| r.word.contains(word))
-                .map(|r| r.match_entry_eq_distance(word)),
-        );
-    }
-
     pub(crate) fn contains_word(&self, word: &str) -> bool {
         self.binary_search(word).is_ok()
     }
 
-    pub(crate) fn ends_with<'a>(&'a self, word: &str, out: &mut Vec<MatchEntry<'a>>) {
-        out.extend(
-            self.0
-                .iter()
-                .filter(|r| r.word.ends_with(word))
-                .map(|r| r.match_entry_eq_distance(word)),
-        );
-    }
-
-    pub(crate) fn fuzzy<'a>(&'a self, dfa: &DFA, word_len: usize, out: &mut Vec<MatchEntry<'a>>) {
-        out.extend(self.0.iter().filter_map(|r| match dfa.eval(r.word) {
-            Distance::AtLeast(_) => None,
-            Distance::Exact(fuzzy_dist) => {
-                let a = r.word.len();
-                let word_dist = min(
-                    a.saturating_sub(word_len) + word_len.saturating_sub(a),
-                    0b111111,
-                ) as u8;
-
-                Some(MatchEntry {
-                    distance: MatchDistance(fuzzy_dist + word_dist),
-                    docs: &r.docs,
-                    word: r.word,
-                })
+    /// Adds the docs of words sorted and deduplicated, merged with the rows in
+    /// a single pass.
+    pub(crate) fn extend_sorted<I>(&mut self, words_docs: I)
+    where
+        I: IntoIterator<Item = (&'static str, RoaringBitmap)>,
+    {
+        let mut old = take(&mut self.0).into_iter().peekable();
+        let mut rows = ChunkedVec::new();
+
+        for (word, docs) in words_docs {
+            while let Some(row) = old.next_if(|row| row.word < word) {
+                rows.push(row);
             }
-        }));
-    }
 
-    pub(crate) fn eq<'a>(&'a self, word: &str, out: &mut Vec<MatchEntry<'a>>) {
-        if let Ok(index) = self.binary_search(word) {
-            out.push(unsafe { self.0.get_unchecked(index) }.match_entry_eq_distance(word));
+            match old.next_if(|row| row.word == word) {
+                Some(mut row) => {
+                    Arc::make_mut(&mut row).docs |= docs;
+                    rows.push(row);
+                }
+                None => rows.push(Arc::new(WordIndexRow { docs, word })),
+            }
         }
+
+        rows.extend(old);
+        self.0 = rows;
     }
 
     pub(crate) fn insert_word_doc(
@@ -75,14 +76,21 @@ impl WordIndex {
                     WordInternResolver::StrInter(intern) => intern.insert(word),
                 };
 
-                self.0.insert(index, WordIndexRow::new(word));
+                self.0.insert(index, Arc::new(WordIndexRow::new(word)));
                 index
             }
         };
 
-        let row = unsafe { self.0.get_unchecked_mut(index) };
-        row.docs.insert(doc_id.0);
-        row.word
+        let row = &self.0[index];
+        let word = row.word;
+
+        // the chunk of the row is only copied when the doc is new.
+        if !row.docs.contains(doc_id.0) {
+            let row = self.0.get_mut(index).expect("row");
+            Arc::make_mut(row).docs.insert(doc_id.0);
+        }
+
+        word
     }
 
     #[cfg(test)]
@@ -90,15 +98,55 @@ impl WordIndex {
         self.0.len()
     }
 
+    /// Reads the rows, resolving the words from the vocabulary of the index.
+    pub(crate) fn read_from<R: Read>(r: &mut R, word_intern: &StrIntern) -> io::Result<Self> {
+        let len = read_len(r)?;
+        let mut rows = ChunkedVec::<Arc<WordIndexRow>>::new();
+        let mut last = None;
+
+        for _ in 0..len {
+            let id = read_u32(r)? as usize;
+
+            if last.is_some_and(|last| last >= id) {
+                return Err(invalid_data("word index is not sorted"));
+            }
+
+            let word = word_intern
+                .get(id)
+                .ok_or_else(|| invalid_data("word not found in vocabulary"))?;
+
+            rows.push(Arc::new(WordIndexRow {
+                docs: read_bitmap(r)?,
+                word,
+            }));
+
+            last = Some(id);
+        }
+
+        Ok(Self(rows))
+    }
+
+    /// Removes the row of a word, returns the interned word with its docs.
+    pub(crate) fn remove_word(&mut self, word: &str) -> Option<(&'static str, RoaringBitmap)> {
+        let index = self.binary_search(word).ok()?;
+        let row = Arc::unwrap_or_clone(self.0.remove(index));
+
+        Some((row.word, row.docs))
+    }
+
     pub(crate) fn remove_word_doc(&mut self, word: &str, doc_id: DocId) -> bool {
         match self.binary_search(word) {
             Ok(index) => {
-                let row = unsafe { self.0.get_unchecked_mut(index) };
-                row.docs.remove(doc_id.0);
-                let is_empty = row.docs.is_empty();
+                let row = &self.0[index];
+                let contains = row.docs.contains(doc_id.0);
+                let is_empty = row.docs.len() == u64::from(contains);
 
                 if is_empty {
+                    // a row about to be empty is dropped rather than copied.
                     self.0.remove(index);
+                } else if contains {
+                    let row = self.0.get_mut(index).expect("row");
+                    Arc::make_mut(row).docs.remove(doc_id.0);
                 }
 
                 is_empty
@@ -107,27 +155,27 @@ impl WordIndex {
         }
     }
 
-    pub(crate) fn starts_with<'a>(&'a self, word: &str, out: &mut Vec<MatchEntry<'a>>) {
-        let index = match self.binary_search(word) {
-            Ok(index) => index,
-            Err(index) => {
-                if index >= self.0.len() {
-                    return;
-                }
+    pub(crate) fn row_docs(&self, index: usize) -> &RoaringBitmap {
+        &self.0[index].docs
+    }
 
-                index
-            }
-        };
+    pub(crate) fn write_to<W: Write>(&self, w: &mut W, word_intern: &StrIntern) -> io::Result<()> {
+        write_len(w, self.0.len())?;
 
-        out.extend(
-            self.0[index..]
-                .iter()
-                .take_while(|r| r.word.starts_with(word))
-                .map(|r| r.match_entry_eq_distance(word)),
-        );
+        for row in &self.0 {
+            let id = word_intern
+                .position(row.word)
+                .ok_or_else(|| invalid_data("word not found in vocabulary"))?;
+
+            write_u32(w, id as u32)?;
+            write_bitmap(w, &row.docs)?;
+        }
+
+        Ok(())
     }
 }
 
+#[derive(Clone)]
 struct WordIndexRow {
     docs: RoaringBitmap,
 
@@ -142,16 +190,179 @@ impl WordIndexRow {
             word,
         }
     }
+}
+
+impl<'a> WordRows<'a> for &'a WordIndex {
+    fn docs(self, index: usize) -> MatchDocs<'a> {
+        MatchDocs::Bitmap(Cow::Borrowed(&self.0[index].docs))
+    }
+
+    fn row_count(self) -> usize {
+        self.0.len()
+    }
+
+    fn word(self, index: usize) -> &'a str {
+        self.0[index].word
+    }
+}
+
+/// Rows of words sorted by word, each associated with the docs containing it.
+///
+/// The query operations are provided on top of it to be shared by the
+/// [WordIndex] and the frozen word index.
+pub(crate) trait WordRows<'a>: Copy {
+    fn docs(self, index: usize) -> MatchDocs<'a>;
+
+    fn row_count(self) -> usize;
+
+    fn word(self, index: usize) -> &'a str;
+
+    fn binary_search(self, word: &str) -> Result<usize, usize> {
+        let index = partition_point(self.row_count(), |i| self.word(i) < word);
+
+        if index < self.row_count() && self.word(index) == word {
+            Ok(index)
+        } else {
+            Err(index)
+        }
+    }
+
+    fn contains(self, word: &str, out: &mut Vec<MatchEntry<'a>>) {
+        out.extend(
+            (0..self.row_count())
+                .filter(|&i| self.word(i).contains(word))
+                .map(|i| self.match_entry_eq_distance(i, word)),
+        );
+    }
+
+    fn ends_with(self, word: &str, out: &mut Vec<MatchEntry<'a>>) {
+        out.extend(
+            (0..self.row_count())
+                .filter(|&i| self.word(i).ends_with(word))
+                .map(|i| self.match_entry_eq_distance(i, word)),
+        );
+    }
+
+    fn eq(self, word: &str, out: &mut Vec<MatchEntry<'a>>) {
+        if let Ok(index) = self.binary_search(word) {
+            out.push(self.match_entry_eq_distance(index, word));
+        }
+    }
+
+    /// Walks the sorted rows as a trie, the DFA states of the prefix shared
+    /// with the previous word being reused, and skips all the words starting
+    /// with a prefix on which the DFA is dead.
+    ///
+    /// Only the words starting with `prefix` are walked.
+    fn fuzzy(self, dfa: &DFA, word_len: usize, prefix: &str, out: &mut Vec<MatchEntry<'a>>) {
+        let row_count = self.row_count();
+        let mut index = partition_point(row_count, |i| self.word(i) < prefix);
+        let mut prev_word = "";
+        let mut states = Vec::<u32>::new();
+
+        while index < row_count {
+            let word = self.word(index);
+
+            if !word.starts_with(prefix) {
+                break;
+            }
+
+            let bytes = word.as_bytes();
+            let common = common_prefix_len(prev_word.as_bytes(), bytes).min(states.len());
+
+            states.truncate(common);
+            prev_word = word;
+
+            let mut state = states.last().copied().unwrap_or(dfa.initial_state());
+            let mut dead_prefix = None;
+
+            for (i, &b) in bytes.iter().enumerate().skip(common) {
+                state = dfa.transition(state, b);
+
+                if state == SINK_STATE {
+                    dead_prefix = Some(&bytes[..=i]);
+                    break;
+                }
+
+                states.push(state);
+            }
+
+            match dead_prefix {
+                Some(prefix) => {
+                    index += partition_point(row_count - index, |i| {
+                        self.word(index + i).as_bytes().starts_with(prefix)
+                    });
+                }
+                None => {
+                    if let Distance::Exact(fuzzy_dist) = dfa.distance(state) {
+                        let a = word.len();
+                        let word_dist = min(
+                            a.saturating_sub(word_len) + word_len.saturating_sub(a),
+                            0b111111,
+                        ) as u8;
+
+                        out.push(MatchEntry {
+                            distance: MatchDistance(fuzzy_dist + word_dist),
+                            docs: self.docs(index),
+                            word,
+                        });
+                    }
+
+                    index += 1;
+                }
+            }
+        }
+    }
 
-    fn match_entry_eq_distance<'a>(&'a self, word: &str) -> MatchEntry<'a> {
-        let d = min(self.word.len() - word.len(), 255) as u8;
+    fn match_entry_eq_distance(self, index: usize, word: &str) -> MatchEntry<'a> {
+        let row_word = self.word(index);
+        let d = min(row_word.len() - word.len(), 255) as u8;
 
         MatchEntry {
             distance: MatchDistance(d),
-            docs: &self.docs,
-            word: self.word,
+            docs: self.docs(index),
+            word: row_word,
         }
     }
+
+    /// Pushes the rows of the candidate words matching `word`, as scanning all
+    /// the rows with the same predicate.
+    fn query_candidates<'b, I>(
+        self,
+        candidates: I,
+        word: &str,
+        is_match: WordPredicate,
+        out: &mut Vec<MatchEntry<'a>>,
+    ) where
+        I: IntoIterator<Item = &'b str>,
+    {
+        out.extend(
+            candidates
+                .into_iter()
+                .filter(|c| is_match(c, word))
+                .filter_map(|c| self.binary_search(c).ok())
+                .map(|i| self.match_entry_eq_distance(i, word)),
+        );
+    }
+
+    fn starts_with(self, word: &str, out: &mut Vec<MatchEntry<'a>>) {
+        let index = match self.binary_search(word) {
+            Ok(index) => index,
+            Err(index) => {
+                if index >= self.row_count() {
+                    return;
+                }
+
+                index
+            }
+        };
+
+        out.extend(
+            (index..self.row_count())
+                .take_while(|&i| self.word(i).starts_with(word))
+                .map(|i| self.match_entry_eq_distance(i, word)),
+        );
+    }
 }
 
 pub(crate) enum WordInternResolver<'a> {
@@ -159,10 +370,33 @@ pub(crate) enum WordInternResolver<'a> {
     StrInter(&'a mut StrIntern),
 }
 
+fn common_prefix_len(a: &[u8], b: &[u8]) -> usize {
+    a.iter().zip(b).take_while(|(a, b)| a == b).count()
+}
+
+fn partition_point<P>(len: usize, mut pred: P) -> usize
+where
+    P: FnMut(usize) -> bool,
+{
+    let (mut low, mut high) = (0, len);
+
+    while low < high {
+        let mid = low + (high - low) / 2;
+
+        if pred(mid) {
+            low = mid + 1;
+        } else {
+            high = mid;
+        }
+    }
+
+    low
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
-    use crate::{str_intern::StrIntern, word_query::create_dfa};
+    use crate::{str_intern::StrIntern, word_query::create_dfa, FuzzyPolicy};
 
     #[test]
     fn insert_remove_doc() {
@@ -192,7 +426,7 @@ mod tests {
         let can_delete = word_index.remove_word_doc(a, DocId::from(1));
         assert!(can_delete);
 
-        assert!(word_index.0.is_empty());
+        assert_eq!(word_index.len(), 0);
     }
 
     #[test]
@@ -226,7 +460,12 @@ mod tests {
         assert_eq!(out, vec![(MatchDistance(0), "balle")]);
 
         out.clear();
-        word_index.fuzzy(&create_dfa("bal").unwrap(), 3, &mut out);
+        word_index.fuzzy(
+            &create_dfa("bal", &FuzzyPolicy::default()).unwrap(),
+            3,
+            "",
+            &mut out,
+        );
         assert_eq!(
             out,
             vec![(MatchDistance(4), "balance"), (MatchDistance(2), "balle")]
@@ -236,4 +475,54 @@ mod tests {
         word_index.starts_with("bala", &mut out);
         assert_eq!(out, vec![(MatchDistance(3), "balance")]);
     }
+
+    #[test]
+    fn fuzzy_skips_dead_prefixes() {
+        let mut word_index = WordIndex::new();
+        let mut intern = StrIntern::new();
+        let words = [
+            "abc",
+            "balance",
+            "balancer",
+            "ballade",
+            "balle",
+            "bas",
+            "boulanger",
+            "bulle",
+            "calcul",
+            "école",
+            "ébauche",
+            "zèbre",
+        ];
+
+        for (i, w) in words.iter().enumerate() {
+            word_index.insert_word_doc(
+                w,
+                WordInternResolver::StrInter(&mut intern),
+                DocId::from(i as u32),
+            );
+        }
+
+        for query in ["bal", "balanse", "écol", "ebauche", "zzz"] {
+            let dfa = create_dfa(query, &FuzzyPolicy::default()).unwrap();
+            let mut out = Vec::new();
+
+            word_index.fuzzy(&dfa, query.len(), "", &mut out);
+
+            // the same words as evaluating the DFA on each word.
+            let mut expected = words
+                .iter()
+                .copied()
+                .filter(|w| matches!(dfa.eval(w), Distance::Exact(_)))
+                .collect::<Vec<_>>();
+
+            let mut actual = out.iter().map(|e| e.word).collect::<Vec<_>>();
+
+            expected.sort_unstable();
+            actual.sort_unstable();
+
+            assert!(!expected.is_empty() || query == "zzz");
+            assert_eq!(actual, expected, "{query}");
+        }
+    }
 }
@@ -1,44 +1,52 @@
-use crate::{presence::Presence, Direction, WordQueryOp};
-use levenshtein_automata::{LevenshteinAutomatonBuilder, DFA};
+use crate::{dfa_cache::prefix_dfa, presence::Presence, Direction, FuzzyPolicy, WordQueryOp};
+use levenshtein_automata::DFA;
 use once_cell::sync::OnceCell;
-use std::fmt::{self, Debug, Formatter};
+use std::{
+    fmt::{self, Debug, Formatter},
+    sync::Arc,
+};
 
+#[derive(Clone)]
 pub(crate) struct WordQuery {
-    backward_dfa: OnceCell<Option<DFA>>,
     backward_word: OnceCell<Box<str>>,
-    dfa: OnceCell<Option<DFA>>,
 
     /// chars len
     pub index: usize,
     pub op: WordQueryOp,
     pub presence: Presence,
+
+    /// the index of the term of the query the word is split from.
+    pub term: usize,
     pub word: Box<str>,
 }
 
 impl WordQuery {
-    pub(crate) fn new(word: Box<str>, op: WordQueryOp, presence: Presence, index: usize) -> Self {
+    pub(crate) fn new(
+        word: Box<str>,
+        op: WordQueryOp,
+        presence: Presence,
+        term: usize,
+        index: usize,
+    ) -> Self {
         Self {
-            backward_dfa: OnceCell::new(),
             backward_word: OnceCell::new(),
-            dfa: OnceCell::new(),
             index,
             op,
             presence,
+            term,
             word,
         }
     }
 
-    pub(crate) fn backward_dfa(&self) -> Option<&DFA> {
-        init_dfa(&self.backward_dfa, self.backward_word())
-    }
-
     pub(crate) fn backward_word(&self) -> &str {
         self.backward_word
             .get_or_init(|| self.word.chars().rev().collect::<String>().into_boxed_str())
     }
 
-    pub(crate) fn dfa(&self) -> Option<&DFA> {
-        init_dfa(&self.dfa, &self.word)
+    /// Gets the DFA matching the directional word with the typos tolerated by
+    /// the policy, `None` when no typo is tolerated.
+    pub(crate) fn dfa(&self, direction: Direction, policy: &FuzzyPolicy) -> Option<Arc<DFA>> {
+        create_dfa(self.directional_word(direction), policy)
     }
 
     pub(crate) fn directional_word(&self, direction: Direction) -> &str {
@@ -66,15 +74,9 @@ impl PartialEq<(&str, WordQueryOp)> for WordQuery {
     }
 }
 
-pub(crate) fn create_dfa(word: &str) -> Option<DFA> {
-    match word.chars().count() {
-        0..=2 => None,
-        3..=5 => Some(LevenshteinAutomatonBuilder::new(1, true).build_prefix_dfa(word)),
-        6..=8 => Some(LevenshteinAutomatonBuilder::new(2, true).build_prefix_dfa(word)),
-        9.. => Some(LevenshteinAutomatonBuilder::new(3, true).build_prefix_dfa(word)),
+pub(crate) fn create_dfa(word: &str, policy: &FuzzyPolicy) -> Option<Arc<DFA>> {
+    match policy.distance(word) {
+        0 => None,
+        distance => Some(prefix_dfa(word, distance, policy.transpositions)),
     }
 }
-
-fn init_dfa<'a>(dfa: &'a OnceCell<Option<DFA>>, word: &str) -> Option<&'a DFA> {
-    dfa.get_or_init(|| create_dfa(word)).as_ref()
-}
@@ -72,3 +72,95 @@ fn match_priority() {
     let o = compare(DocId::from(0), &results, DocId::from(1), &results);
     assert_eq!(o, Ordering::Less);
 }
+
+#[test]
+fn top_pages() {
+    let searcher = searcher(&[
+        "count",
+        "country",
+        "count topic",
+        "topic count",
+        "encours",
+        "counting",
+        "account",
+        "count",
+    ]);
+
+    let results = searcher.query(&SearchQuery::new(0, "count topic"));
+    let mut all = results.iter().collect::<Vec<_>>();
+
+    all.sort_by(|l, r| compare(*l, &results, *r, &results).then_with(|| l.cmp(r)));
+
+    assert_eq!(results.top(0, 3), all[..3]);
+    assert_eq!(results.top(2, 3), all[2..5]);
+    assert_eq!(results.top(5, 100), all[5..]);
+    assert!(results.top(100, 10).is_empty());
+
+    assert_eq!(results.sorted_iter().collect::<Vec<_>>(), all);
+    assert_eq!(results.sorted_iter().nth(4), Some(all[4]));
+}
+
+#[test]
+fn top_pages_with_priorities() {
+    let mut searcher = Searcher::new();
+    searcher.set_attribute("0".into(), AttrProps::default().priority(0));
+    searcher.set_attribute("1".into(), AttrProps::default().priority(1));
+
+    let docs = [
+        ("count down", "topic"),
+        ("topic", "count"),
+        ("account", "count topic"),
+        ("counting topic", ""),
+        ("", "topic count"),
+        ("count", "count"),
+        ("encours", "country"),
+        ("topic count", "topic"),
+    ];
+
+    for (i, (zero, one)) in docs.into_iter().enumerate() {
+        let id = DocId::from(i as u32);
+        searcher.insert_doc_attribute(id, "0", zero);
+        searcher.insert_doc_attribute(id, "1", one);
+    }
+
+    let results = searcher.query(&SearchQuery::new(0, "count topic"));
+    let mut all = results.iter().collect::<Vec<_>>();
+
+    all.sort_by(|l, r| compare(*l, &results, *r, &results).then_with(|| l.cmp(r)));
+
+    for offset in 0..all.len() {
+        assert_eq!(
+            results.top(offset, 2),
+            all[offset..(offset + 2).min(all.len())]
+        );
+    }
+
+    assert_eq!(results.sorted_iter().collect::<Vec<_>>(), all);
+}
+
+#[test]
+fn rank_keys() {
+    let searcher = searcher(&[
+        "count",
+        "country",
+        "count topic",
+        "topic count",
+        "encours",
+        "counting",
+        "account",
+        "count",
+    ]);
+
+    let results = searcher.query(&SearchQuery::new(0, "count topic"));
+    let ids = results.iter().collect::<Vec<_>>();
+
+    for l in &ids {
+        for r in &ids {
+            let keys = results.rank_key(*l).cmp(&results.rank_key(*r));
+            assert_eq!(keys, compare(*l, &results, *r, &results));
+        }
+    }
+
+    assert_eq!(results.rank_key(DocId::from(4)), None);
+    assert_eq!(results.sorted_doc_ids(), results.top(0, usize::MAX));
+}